@@ -1,11 +1,87 @@
-use super::{event_handler::ModuleEventHandler, modules::DragonBotModuleInstance};
+use super::{
+    event_handler::ModuleEventHandler, modules::DragonBotModuleInstance,
+    permissions::ModulePermission,
+};
 use crate::{
     core::module::{get_module, get_module_by_id},
-    module::{errors::ModuleError, module_manager::ModuleManager},
+    module::{
+        config::DragonModuleConfigurable, errors::ModuleError, module_manager::ModuleManager,
+        permissions::PermissionsManager,
+    },
     util::get_all_guilds,
 };
 use log::{debug, info, warn};
-use serenity::all::{Builder, CacheHttp, CommandInteraction, Context, CreateCommand, GuildId};
+use serenity::all::{
+    Builder, CacheHttp, CommandDataOptionValue, CommandInteraction, Context, CreateCommand,
+    CreateEmbed, CreateInteractionResponseFollowup, GuildId, Permissions,
+};
+
+/// the subcommand group and subcommand names of the invoked command, e.g. `["group", "create"]`.
+pub fn subcommand_path(command: &CommandInteraction) -> Vec<&str> {
+    let mut path = vec![];
+    let mut options = &command.data.options;
+    while let Some(option) = options.first() {
+        match &option.value {
+            CommandDataOptionValue::SubCommandGroup(nested)
+            | CommandDataOptionValue::SubCommand(nested) => {
+                path.push(option.name.as_str());
+                options = nested;
+            }
+            _ => break,
+        }
+    }
+    path
+}
+
+fn path_matches(pattern: &str, path: &[&str]) -> bool {
+    let pattern: Vec<&str> = pattern.split_whitespace().collect();
+    pattern.len() == path.len()
+        && pattern
+            .iter()
+            .zip(path)
+            .all(|(pattern, name)| *pattern == "*" || pattern == name)
+}
+
+/// the first declared requirement matching the path, see `subcommand_permissions`.
+/// `None` if the module does not declare the subcommand.
+pub fn required_permission(
+    module: &DragonBotModuleInstance,
+    path: &[&str],
+) -> Option<Option<ModulePermission>> {
+    module
+        .subcommand_permissions()
+        .into_iter()
+        .find(|(pattern, _)| path_matches(pattern, path))
+        .map(|(_, permission)| permission)
+}
+
+/// the Discord defaults shared by every permission the module's subcommands require. commands
+/// with a subcommand anyone may use, or in guilds granting anything in their permission tree,
+/// stay visible, the grants may reach anyone.
+async fn default_member_permissions(
+    guild: GuildId,
+    module: &DragonBotModuleInstance,
+) -> Option<Permissions> {
+    let permissions = module.subcommand_permissions();
+    if permissions.is_empty() {
+        return None;
+    }
+    let mut shared = Permissions::all();
+    for (_, permission) in permissions {
+        shared &= permission?.default_permissions()?;
+    }
+    if shared.is_empty() {
+        return None;
+    }
+    match PermissionsManager::get_full_config(guild).await {
+        Ok(guild_config) if !guild_config.has_grants() => Some(shared),
+        Ok(_) => None,
+        Err(err) => {
+            warn!("failed to read the permissions of {guild}: {err:?}");
+            None
+        }
+    }
+}
 
 pub trait DragonModuleCommand {
     fn command_builder(&self, _guild: GuildId) -> impl Future<Output = Option<CreateCommand>> {
@@ -20,22 +96,80 @@ pub trait DragonModuleCommand {
         async { Ok(()) }
     }
 
+    /// the permission each subcommand requires, checked before `command_handle` runs. paths are
+    /// the subcommand group and subcommand names, e.g. `"deactivate"` or `"group create"`, a `*`
+    /// matches any name and the first matching path applies. `None` marks a subcommand anyone
+    /// may use, undeclared subcommands check their permissions themselves.
+    fn subcommand_permissions(&self) -> Vec<(&'static str, Option<ModulePermission>)> {
+        vec![]
+    }
+
+    /// re-registers the module's command after its config changed.
+    fn command_depends_on_config(&self) -> bool {
+        false
+    }
+
+    /// lists the declared subcommands and the permission each requires.
     fn command_help(
         &self,
-        _ctx: &Context,
-        _interaction: &CommandInteraction,
+        ctx: &Context,
+        interaction: &CommandInteraction,
     ) -> impl Future<Output = ()> {
-        async { todo!("default help handler") }
+        let mut lines: Vec<String> = self
+            .subcommand_permissions()
+            .into_iter()
+            .map(|(path, permission)| {
+                format!(
+                    "`/{} {path}`: {}",
+                    interaction.data.name,
+                    match permission {
+                        Some(permission) =>
+                            format!("requires `{}:{}`", permission.module(), permission.id()),
+                        None => "anyone".to_string(),
+                    }
+                )
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push("No subcommands declared.".to_string());
+        }
+        async move {
+            if let Err(error) = interaction
+                .create_followup(
+                    ctx.http(),
+                    CreateInteractionResponseFollowup::new().embed(
+                        CreateEmbed::new()
+                            .title(format!("/{}", interaction.data.name))
+                            .description(lines.join("\n")),
+                    ),
+                )
+                .await
+            {
+                warn!("failed to send command help: {error}");
+            }
+        }
     }
 }
 
 impl ModuleEventHandler {
+    /// the module's command, hidden from members without its default Discord permissions.
+    async fn guild_command_builder(
+        guild: GuildId,
+        module: &DragonBotModuleInstance,
+    ) -> Option<CreateCommand> {
+        let builder = module.command_builder(guild).await?;
+        Some(match default_member_permissions(guild, module).await {
+            Some(permissions) => builder.default_member_permissions(permissions),
+            None => builder,
+        })
+    }
+
     pub async fn register_guild_module_command(
         ctx: &Context,
         guild: GuildId,
         module: &DragonBotModuleInstance,
     ) {
-        let builder = module.command_builder(guild).await;
+        let builder = Self::guild_command_builder(guild, module).await;
         if builder.is_none() {
             return;
         }
@@ -62,12 +196,10 @@ impl ModuleEventHandler {
         if let Some(command) = commands
             .unwrap()
             .iter()
-            .filter(|command| command.name == module.module_id())
-            .nth(0)
+            .find(|command| command.name == module.module_id())
+            && let Err(error) = ctx.http().delete_guild_command(guild, command.id).await
         {
-            if let Err(error) = ctx.http().delete_guild_command(guild, command.id).await {
-                warn!("Failed to delete old command: {error}");
-            }
+            warn!("Failed to delete old command: {error}");
         }
     }
 
@@ -75,55 +207,59 @@ impl ModuleEventHandler {
         info!("Initializing guild commands");
 
         for guild in get_all_guilds(ctx).await? {
-            debug!("init_guild_commands: {}", guild.id);
-
-            let current_commands = ctx.http().get_guild_commands(guild.id).await;
-            for command in current_commands.iter().flatten() {
-                if let Err(error) = ctx.http().delete_guild_command(guild.id, command.id).await {
-                    warn!("Failed to delete old command: {error}");
-                } else {
-                    debug!("deleted old command: {}", command.name);
-                }
-            }
+            Self::reset_guild_commands(ctx, guild.id).await?;
+        }
 
-            let mut wanted_commands = vec![];
+        Ok(())
+    }
 
-            let module = get_module::<ModuleManager>()?;
-            let manager: &ModuleManager = module.module();
+    /// drops every command in the guild and registers the commands of all active modules again.
+    pub async fn reset_guild_commands(ctx: &Context, guild: GuildId) -> Result<(), ModuleError> {
+        debug!("reset_guild_commands: {guild}");
 
-            let active_modules = manager.get_all_active_module_ids(guild.id).await?.clone();
-            if let Some(manager_command) = manager.command_builder(guild.id).await {
-                wanted_commands.push(manager_command.clone());
+        let current_commands = ctx.http().get_guild_commands(guild).await;
+        for command in current_commands.iter().flatten() {
+            if let Err(error) = ctx.http().delete_guild_command(guild, command.id).await {
+                warn!("Failed to delete old command: {error}");
+            } else {
+                debug!("deleted old command: {}", command.name);
             }
+        }
 
-            debug!("getting wanted commands");
-            for active_module in active_modules {
-                let module = get_module_by_id(&active_module);
-                if module.is_err() {
-                    warn!("skipping invalid module {active_module}!");
-                    continue;
-                }
-                if let Some(command) = module.unwrap().command_builder(guild.id).await {
-                    wanted_commands.push(command);
-                }
-            }
+        let mut wanted_commands = vec![];
 
-            let mut created_commands = vec![];
-            for new in wanted_commands {
-                let command = new.execute(ctx.http(), (Some(guild.id), None)).await;
-                if let Err(e) = &command {
-                    warn!("Failed to create command: {e}");
-                    continue;
-                } else {
-                    created_commands.push(command.unwrap());
-                }
+        let module = get_module::<ModuleManager>()?;
+        let manager: &ModuleManager = module.module();
+
+        let active_modules = manager.get_command_module_ids(guild).await?;
+
+        debug!("getting wanted commands");
+        for active_module in active_modules {
+            let module = get_module_by_id(&active_module);
+            if module.is_err() {
+                warn!("skipping invalid module {active_module}!");
+                continue;
+            }
+            if let Some(command) = Self::guild_command_builder(guild, module.unwrap()).await {
+                wanted_commands.push(command);
             }
+        }
 
-            for command in created_commands {
-                info!("Created command: {}|{}", command.name, command.id);
+        let mut created_commands = vec![];
+        for new in wanted_commands {
+            let command = new.execute(ctx.http(), (Some(guild), None)).await;
+            if let Err(e) = &command {
+                warn!("Failed to create command: {e}");
+                continue;
+            } else {
+                created_commands.push(command.unwrap());
             }
         }
 
+        for command in created_commands {
+            info!("Created command: {}|{}", command.name, command.id);
+        }
+
         Ok(())
     }
 }
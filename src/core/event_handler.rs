@@ -1,12 +1,24 @@
-use super::module::get_module_by_id_mut;
+use super::{
+    commands::{required_permission, subcommand_path},
+    module::get_module_by_id_mut,
+    permissions::assert_permission,
+};
 use crate::{
-    core::{module::get_module, modules::DragonBotModuleInstance},
-    module::module_manager::ModuleManager,
+    core::{
+        module::{DragonBotModule, get_module},
+        modules::DragonBotModuleInstance,
+    },
+    module::{
+        config::watcher::watch_config_dir,
+        errors::{ErrorManager, ErrorOrigin, ModuleError},
+        module_manager::ModuleManager,
+    },
 };
 use log::{debug, info, warn};
 use serenity::{
     all::{
-        CacheHttp, Context, CreateInteractionResponseFollowup, EventHandler, Interaction, Ready,
+        CacheHttp, CommandInteraction, Context, CreateInteractionResponseFollowup, EventHandler,
+        Interaction, Ready,
     },
     async_trait,
 };
@@ -18,13 +30,13 @@ impl ModuleEventHandler {
         info!("Initializing modules...");
 
         for module in DragonBotModuleInstance::all_module_ids() {
-            match get_module_by_id_mut(module).map(async |module| module.init(ctx).await) {
-                Err(err) => warn!("failed to init {module}: {err:?}"),
-                Ok(result) => {
-                    if let Err(err) = result.await {
-                        warn!("failed to init {module}: {err:?}");
-                    }
-                }
+            let result = match get_module_by_id_mut(module) {
+                Ok(instance) => instance.init(ctx).await,
+                Err(err) => Err(ModuleError::from(err)),
+            };
+            if let Err(err) = result {
+                warn!("failed to init {module}");
+                ErrorManager::report(ctx, ErrorOrigin::new(module), &err).await;
             }
         }
 
@@ -50,6 +62,7 @@ impl EventHandler for ModuleEventHandler {
     async fn ready(&self, ctx: Context, _ready: Ready) {
         self.init_modules(&ctx).await;
         self.init_commands(&ctx).await;
+        watch_config_dir(&ctx).await;
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
@@ -76,7 +89,13 @@ impl EventHandler for ModuleEventHandler {
                     return;
                 }
                 Err(err) => {
-                    warn!("Failed to query module active state {target_module}: {err:?}",);
+                    warn!("Failed to query module active state {target_module}");
+                    ErrorManager::report(
+                        &ctx,
+                        ErrorOrigin::new(ModuleManager::module_id()).guild(guild),
+                        &err,
+                    )
+                    .await;
                     return;
                 }
                 _ => {}
@@ -96,20 +115,56 @@ impl EventHandler for ModuleEventHandler {
             }
 
             let module = module.unwrap();
-            let result = module.command_handle(&ctx, &command).await;
-            if let Err(error) = result {
-                if let Err(error) = command
-                    .create_followup(
-                        ctx.http(),
-                        CreateInteractionResponseFollowup::new()
-                            .content(format!("Command failed: `{error:?}`"))
-                            .ephemeral(true),
+            let path = subcommand_path(&command);
+            let module_id = module.module_id();
+            let origin = || {
+                ErrorOrigin::new(module_id)
+                    .guild(guild)
+                    .channel(command.channel_id)
+                    .user(command.user.id)
+                    .command(
+                        [vec![command.data.name.as_str()], path.clone()]
+                            .concat()
+                            .join(" "),
                     )
-                    .await
-                {
-                    warn!("Failed to send error response to interaction: {error}");
+            };
+            if let Some(Some(permission)) = required_permission(module, &path) {
+                let member = command.member.as_ref().unwrap();
+                match assert_permission(&ctx, &command, member, permission).await {
+                    Ok(true) => {}
+                    Ok(false) => return,
+                    Err(error) => {
+                        warn!("Failed to check command permission");
+                        command_failed(&ctx, &command, origin(), &error).await;
+                        return;
+                    }
                 }
             }
+
+            if let Err(error) = module.command_handle(&ctx, &command).await {
+                command_failed(&ctx, &command, origin(), &error).await;
+            }
         }
     }
 }
+
+/// records the error and tells the user what went wrong.
+async fn command_failed(
+    ctx: &Context,
+    command: &CommandInteraction,
+    origin: ErrorOrigin,
+    error: &ModuleError,
+) {
+    let reference = ErrorManager::report(ctx, origin, error).await;
+    if let Err(error) = command
+        .create_followup(
+            ctx.http(),
+            CreateInteractionResponseFollowup::new()
+                .content(ErrorManager::user_message(error, reference))
+                .ephemeral(true),
+        )
+        .await
+    {
+        warn!("Failed to send error response to interaction: {error}");
+    }
+}
@@ -4,10 +4,17 @@ use super::{
     commands::DragonModuleCommand, modules::DragonBotModuleInstance,
     permissions::DragonModulePermission,
 };
-use crate::module::{config::DragonModuleConfigurable, errors::ModuleError};
+use crate::module::{
+    config::DragonModuleConfigurable,
+    errors::{ModuleError, UserFacingError},
+};
 use log::debug;
-use serenity::all::Context;
-use std::{collections::HashMap, sync::OnceLock};
+use serenity::all::{Context, GuildId};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    sync::OnceLock,
+};
 use strum::IntoEnumIterator;
 
 #[derive(Debug)]
@@ -16,6 +23,21 @@ pub enum GetModuleError {
     ModuleBlocked,
 }
 
+impl Display for GetModuleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GetModuleError::ModuleNotFound => write!(f, "That module does not exist."),
+            GetModuleError::ModuleBlocked => write!(f, "That module is busy, try again later."),
+        }
+    }
+}
+
+impl UserFacingError for GetModuleError {
+    fn is_user_error(&self) -> bool {
+        true
+    }
+}
+
 pub trait DragonBotModule
 where
     Self: Default + DragonModulePermission + DragonModuleCommand + DragonModuleConfigurable,
@@ -28,6 +50,24 @@ where
     fn init(&mut self, _ctx: &Context) -> impl Future<Output = Result<(), ModuleError>> {
         async { Ok(()) }
     }
+
+    fn on_config_reloaded(
+        &mut self,
+        _ctx: &Context,
+        _guild: GuildId,
+    ) -> impl Future<Output = Result<(), ModuleError>> {
+        async { Ok(()) }
+    }
+
+    /// called by `ConfigManager` after a field of the guild's config was saved.
+    fn on_config_changed(
+        &mut self,
+        _ctx: &Context,
+        _guild: GuildId,
+        _field: &str,
+    ) -> impl Future<Output = Result<(), ModuleError>> {
+        async { Ok(()) }
+    }
 }
 
 macro_rules! impl_from {
@@ -67,10 +107,78 @@ macro_rules! impl_from {
                     )+
                 }
             }
-            pub async fn save(self, guild: GuildId) -> Result<(), ModuleError> {
+            pub fn default_config_entry(&self, field: &str) -> Result<ConfigValue, ConfigFieldError> {
+                match self {
+                    $(
+                        ModuleConfigHolder::$type(_) => <$type as DragonModuleConfigurable>::Config::default().get_config_entry(field),
+                    )+
+                }
+            }
+            pub async fn reset_config_entry(&mut self, field: &str) -> Result<(), ConfigFieldError> {
+                let default = self.default_config_entry(field)?;
+                self.set_config_entry(field, default).await
+            }
+            pub async fn changes_from_disk(&self, guild: GuildId) -> Result<Vec<FieldChange>, ModuleError> {
+                match self {
+                    $(
+                        ModuleConfigHolder::$type(config) => Ok(diff_config_fields(&$type::get_full_config(guild).await?, config)),
+                    )+
+                }
+            }
+            pub fn module_id(&self) -> &'static str {
+                match self {
+                    $(
+                        ModuleConfigHolder::$type(_) => $type::module_id(),
+                    )+
+                }
+            }
+            pub async fn save(self, guild: GuildId, user: UserId) -> Result<(), ModuleError> {
+                self.save_fields(guild, user, &[], &[]).await
+            }
+            /// saves the guild's config, marking `set` as set by the guild and `inherit` as
+            /// inherited from the global config, see `stage_guild_config`.
+            pub async fn save_fields(self, guild: GuildId, user: UserId, set: &[&str], inherit: &[&str]) -> Result<(), ModuleError> {
+                let module = self.module_id();
+                let changes = self.changes_from_disk(guild).await?;
+                match &self {
+                    $(
+                        ModuleConfigHolder::$type(config) => $type::stage_guild_config(guild, config, set, inherit).await?.commit().await?,
+                    )+
+                }
+                // the config is already saved, a retry would record the change twice
+                if let Err(err) = record_history(Some(guild), module, changes, user).await {
+                    error!("failed to record {module} config history for {guild}: {err:?}");
+                }
+                Ok(())
+            }
+            pub async fn save_global(self, user: UserId) -> Result<(), ModuleError> {
+                let module = self.module_id();
+                let changes = match &self {
+                    $(
+                        ModuleConfigHolder::$type(config) => diff_config_fields(&$type::get_global_config().await?, config),
+                    )+
+                };
                 match self {
                     $(
-                        ModuleConfigHolder::$type(config) => $type::set_full_config(guild, config).await,
+                        ModuleConfigHolder::$type(config) => $type::set_global_config(config).await?,
+                    )+
+                }
+                if let Err(err) = record_history(None, module, changes, user).await {
+                    error!("failed to record {module} global config history: {err:?}");
+                }
+                Ok(())
+            }
+            pub async fn stage(&self, guild: GuildId) -> Result<StagedConfig, ModuleError> {
+                match self {
+                    $(
+                        ModuleConfigHolder::$type(config) => $type::stage_full_config(guild, config).await,
+                    )+
+                }
+            }
+            pub fn to_json(&self) -> Result<serde_json::Value, ModuleError> {
+                match self {
+                    $(
+                        ModuleConfigHolder::$type(config) => Ok(serde_json::to_value(config).map_err(ConfigError::SerdeError)?),
                     )+
                 }
             }
@@ -80,7 +188,39 @@ macro_rules! impl_from {
             pub async fn init(&mut self, ctx: &Context) -> Result<(), ModuleError>{
                 match self {
                     $(
-                        DragonBotModuleInstance::$type(module) => module.init(ctx).await,
+                        DragonBotModuleInstance::$type(module) => catch_panic(module.init(ctx)).await,
+                    )+
+                }
+            }
+
+            pub async fn on_config_reloaded(&mut self, ctx: &Context, guild: GuildId) -> Result<(), ModuleError> {
+                match self {
+                    $(
+                        DragonBotModuleInstance::$type(module) => catch_panic(module.on_config_reloaded(ctx, guild)).await,
+                    )+
+                }
+            }
+
+            pub async fn on_config_changed(&mut self, ctx: &Context, guild: GuildId, field: &str) -> Result<(), ModuleError> {
+                match self {
+                    $(
+                        DragonBotModuleInstance::$type(module) => catch_panic(module.on_config_changed(ctx, guild, field)).await,
+                    )+
+                }
+            }
+
+            pub fn subcommand_permissions(&self) -> Vec<(&'static str, Option<ModulePermission>)> {
+                match self {
+                    $(
+                        DragonBotModuleInstance::$type(instance) => instance.subcommand_permissions(),
+                    )+
+                }
+            }
+
+            pub fn command_depends_on_config(&self) -> bool {
+                match self {
+                    $(
+                        DragonBotModuleInstance::$type(instance) => instance.command_depends_on_config(),
                     )+
                 }
             }
@@ -92,17 +232,22 @@ macro_rules! impl_from {
             ) -> Result<(), ModuleError> {
                 match self {
                     $(
-                        DragonBotModuleInstance::$type(instance) => instance.command_handle(ctx, interaction).await,
+                        DragonBotModuleInstance::$type(instance) => catch_panic(instance.command_handle(ctx, interaction)).await,
                     )+
                 }
             }
 
+            /// `None` if the builder panicked, the module then has no command in the guild.
             pub async fn command_builder(&self, guild: GuildId) -> Option<CreateCommand> {
-                match self {
+                let builder = match self {
                     $(
-                        DragonBotModuleInstance::$type(instance) => instance.command_builder(guild).await,
+                        DragonBotModuleInstance::$type(instance) => AssertUnwindSafe(instance.command_builder(guild)).catch_unwind().await,
                     )+
-                }
+                };
+                builder.unwrap_or_else(|payload| {
+                    error!("{} command builder panicked in {guild}: {}", self.module_id(), panic_message(payload));
+                    None
+                })
             }
 
             pub async fn all_permissions(&self) -> Vec<ModulePermission> {
@@ -121,6 +266,22 @@ macro_rules! impl_from {
                 }
             }
 
+            pub fn config_view_permission(&self) -> Option<ModulePermission> {
+                match self {
+                    $(
+                        DragonBotModuleInstance::$type(_) => $type::config_view_permission(),
+                    )+
+                }
+            }
+
+            pub fn config_edit_permission(&self) -> Option<ModulePermission> {
+                match self {
+                    $(
+                        DragonBotModuleInstance::$type(_) => $type::config_edit_permission(),
+                    )+
+                }
+            }
+
             pub async fn get_config(&self, guild: GuildId) -> Result<ModuleConfigHolder, ModuleError> {
                 match self {
                     $(
@@ -129,6 +290,30 @@ macro_rules! impl_from {
                 }
             }
 
+            pub async fn inherited_fields(&self, guild: GuildId) -> Result<HashSet<&'static str>, ModuleError> {
+                match self {
+                    $(
+                        DragonBotModuleInstance::$type(_) => $type::inherited_fields(guild).await,
+                    )+
+                }
+            }
+
+            pub async fn get_global_config(&self) -> Result<ModuleConfigHolder, ModuleError> {
+                match self {
+                    $(
+                        DragonBotModuleInstance::$type(_) => Ok(ModuleConfigHolder::$type($type::get_global_config().await?)),
+                    )+
+                }
+            }
+
+            pub fn config_from_json(&self, json: serde_json::Value) -> Result<ModuleConfigHolder, ModuleError> {
+                match self {
+                    $(
+                        DragonBotModuleInstance::$type(_) => Ok(ModuleConfigHolder::$type(serde_json::from_value(json).map_err(ConfigError::SerdeError)?)),
+                    )+
+                }
+            }
+
             pub fn module_id(&self) -> &'static str {
                 match self {
                     $(
@@ -2,24 +2,36 @@ use crate::core::commands::DragonModuleCommand;
 use crate::core::module::DragonBotModule;
 use crate::core::permissions::DragonModulePermission;
 use crate::core::permissions::ModulePermission;
+use crate::module::bot_admin::config::BotAdminConfig;
+use crate::module::config::ConfigError;
 use crate::module::config::DragonModuleConfigurable;
 use crate::module::config::ModuleConfig;
 use crate::module::config::NoConfig;
+use crate::module::config::StagedConfig;
 use crate::module::config::entry::ConfigField;
 use crate::module::config::entry::ConfigFieldError;
 use crate::module::config::entry::ConfigValue;
+use crate::module::config::history::FieldChange;
+use crate::module::config::history::diff_config_fields;
+use crate::module::config::history::record_history;
 use crate::module::errors::ModuleError;
+use crate::module::errors::config::ErrorManagerConfig;
+use crate::module::errors::panic::catch_panic;
+use crate::module::errors::panic::panic_message;
 use crate::module::module_manager::ModuleManager;
 use crate::module::module_manager::config::ModuleManagerConfig;
 use crate::module::permissions::config::PermissionsManagerConfig;
 use crate::module::tg_verify::config::TgVerifyConfig;
 use crate::module::tgdb::config::TgDbConfig;
 use crate::module::{
-    config::ConfigManager, errors::ErrorManager, permissions::PermissionsManager,
-    tg_verify::TgVerify, tgdb::TgDb,
+    bot_admin::BotAdmin, config::ConfigManager, errors::ErrorManager,
+    permissions::PermissionsManager, tg_verify::TgVerify, tgdb::TgDb,
 };
-use serenity::all::{CommandInteraction, Context, CreateCommand, GuildId};
-use std::collections::HashMap;
+use log::error;
+use serenity::all::{CommandInteraction, Context, CreateCommand, GuildId, UserId};
+use serenity::futures::FutureExt;
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
 use strum::EnumIter;
 
 #[derive(EnumIter)]
@@ -30,6 +42,7 @@ pub enum DragonBotModuleInstance {
     PermissionsManager(PermissionsManager),
     ErrorManager(ErrorManager),
     ModuleManager(ModuleManager),
+    BotAdmin(BotAdmin),
 }
 
 pub enum ModuleConfigHolder {
@@ -37,8 +50,9 @@ pub enum ModuleConfigHolder {
     TgVerify(TgVerifyConfig),
     ConfigManager(NoConfig),
     PermissionsManager(PermissionsManagerConfig),
-    ErrorManager(NoConfig),
+    ErrorManager(ErrorManagerConfig),
     ModuleManager(ModuleManagerConfig),
+    BotAdmin(BotAdminConfig),
 }
 
 impl_from!(
@@ -48,4 +62,5 @@ impl_from!(
     PermissionsManager
     ErrorManager
     ModuleManager
+    BotAdmin
 );
@@ -1,16 +1,33 @@
 use crate::{
     core::module::get_module,
-    module::{errors::ModuleError, permissions::PermissionsManager},
+    module::{commands::CommandError, errors::ModuleError, permissions::PermissionsManager},
 };
 use log::warn;
 use serenity::all::{
-    CacheHttp, CommandInteraction, Context, CreateInteractionResponseFollowup, Member,
+    CacheHttp, ChannelId, CommandInteraction, Context, CreateInteractionResponseFollowup, Member,
+    Permissions, UserId,
 };
+use std::collections::HashSet;
+use tokio::sync::OnceCell;
 
-pub struct ModulePermission(&'static str, &'static str, &'static str);
+static BOT_OWNERS: OnceCell<HashSet<UserId>> = OnceCell::const_new();
+
+#[derive(Clone, Copy)]
+pub struct ModulePermission(
+    &'static str,
+    &'static str,
+    &'static str,
+    Option<Permissions>,
+);
 impl ModulePermission {
     pub const fn new(module: &'static str, id: &'static str, desc: &'static str) -> Self {
-        Self(module, id, desc)
+        Self(module, id, desc, None)
+    }
+    /// members holding these Discord permissions have the permission unless a rule of the
+    /// permission tree decides otherwise.
+    pub const fn discord_default(mut self, permissions: Permissions) -> Self {
+        self.3 = Some(permissions);
+        self
     }
     pub fn module(&self) -> &str {
         self.0
@@ -21,6 +38,9 @@ impl ModulePermission {
     pub fn desc(&self) -> &str {
         self.2
     }
+    pub fn default_permissions(&self) -> Option<Permissions> {
+        self.3
+    }
 }
 
 impl PartialEq for ModulePermission {
@@ -35,22 +55,33 @@ pub trait DragonModulePermission {
     }
 }
 
-pub async fn assert_permission(
+/// `channel` is where the permission is used, channel scoped grants apply there.
+pub async fn check_permission(
     ctx: &Context,
-    command: &CommandInteraction,
     member: &Member,
+    channel: Option<ChannelId>,
     permission: ModulePermission,
 ) -> Result<bool, ModuleError> {
-    if member.permissions.is_some_and(|perm| perm.administrator()) {
+    if member.permissions.is_some_and(|perm| perm.administrator())
+        || is_bot_owner(ctx, member.user.id).await?
+    {
         return Ok(true);
     }
 
     let permissions = get_module::<PermissionsManager>()?;
-    if !permissions
+    permissions
         .module::<PermissionsManager>()
-        .has_permission(member, permission)
-        .await?
-    {
+        .has_permission(ctx, member, channel, permission)
+        .await
+}
+
+pub async fn assert_permission(
+    ctx: &Context,
+    command: &CommandInteraction,
+    member: &Member,
+    permission: ModulePermission,
+) -> Result<bool, ModuleError> {
+    if !check_permission(ctx, member, Some(command.channel_id), permission).await? {
         if let Err(error) = command
             .create_followup(
                 ctx.http(),
@@ -66,3 +97,43 @@ pub async fn assert_permission(
     }
     Ok(true)
 }
+
+/// the owner of the bot application, or every member of the team owning it.
+pub async fn is_bot_owner(ctx: &Context, user: UserId) -> Result<bool, ModuleError> {
+    let owners = BOT_OWNERS
+        .get_or_try_init(|| async {
+            let info = ctx
+                .http()
+                .get_current_application_info()
+                .await
+                .map_err(CommandError::from)?;
+            let mut owners: HashSet<UserId> = info.owner.iter().map(|owner| owner.id).collect();
+            if let Some(team) = info.team {
+                owners.extend(team.members.iter().map(|member| member.user.id));
+            }
+            Ok::<_, ModuleError>(owners)
+        })
+        .await?;
+    Ok(owners.contains(&user))
+}
+
+pub async fn assert_bot_owner(
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<bool, ModuleError> {
+    if !is_bot_owner(ctx, command.user.id).await? {
+        if let Err(error) = command
+            .create_followup(
+                ctx.http(),
+                CreateInteractionResponseFollowup::new()
+                    .ephemeral(true)
+                    .content("Only bot owners can use this command."),
+            )
+            .await
+        {
+            warn!("failed to send bot owner assertion error response: {error}");
+        }
+        return Ok(false);
+    }
+    Ok(true)
+}
@@ -2,6 +2,7 @@ use chrono::Utc;
 use core::{event_handler::ModuleEventHandler, module::init_module_map};
 use fern::colors::{Color, ColoredLevelConfig};
 use log::{LevelFilter, error, info};
+use module::config::secret::init_secret_key;
 use serenity::{Client, all::GatewayIntents};
 use std::env;
 use tokio::main;
@@ -34,6 +35,9 @@ async fn main() {
         .expect("failed to set fern as logger");
 
     data_path().await.expect("failed to init data path");
+    init_secret_key()
+        .await
+        .expect("failed to init config secret key");
     init_module_map();
 
     let token = env::var("DISCORD_TOKEN");
@@ -53,9 +57,8 @@ async fn main() {
         return;
     }
 
-    let run_result = client.unwrap().start_autosharded().await;
-    if run_result.is_err() {
-        error!("failed to run discord client: {}", run_result.unwrap_err());
+    if let Err(error) = client.unwrap().start_autosharded().await {
+        error!("failed to run discord client: {error}");
         return;
     }
 
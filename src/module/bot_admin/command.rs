@@ -0,0 +1,327 @@
+use super::{BotAdmin, BotAdminError};
+use crate::{
+    core::{
+        commands::DragonModuleCommand,
+        event_handler::ModuleEventHandler,
+        module::{DragonBotModule, get_module, get_module_by_id, get_module_mut},
+        permissions::assert_bot_owner,
+    },
+    module::{
+        errors::{ErrorManager, ModuleError},
+        module_manager::ModuleManager,
+    },
+    util::get_all_guilds,
+};
+use log::warn;
+use serenity::all::{
+    CacheHttp, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponseFollowup, GuildId,
+};
+
+const EMBED_DESCRIPTION_MAX_LEN: usize = 4000;
+const RECENT_ERRORS: usize = 20;
+
+impl DragonModuleCommand for BotAdmin {
+    async fn command_builder(&self, guild: GuildId) -> Option<CreateCommand> {
+        match Self::staff_guild().await {
+            Ok(Some(staff_guild)) if staff_guild == guild => {}
+            Ok(_) => return None,
+            Err(err) => {
+                warn!("failed to read the staff guild: {err:?}");
+                return None;
+            }
+        }
+
+        let guild_option =
+            CreateCommandOption::new(CommandOptionType::String, "guild", "the guild id")
+                .required(true);
+        let module_option =
+            CreateCommandOption::new(CommandOptionType::String, "module", "the module id")
+                .required(true);
+        Some(
+            CreateCommand::new(self.id())
+                .description("bot owner commands")
+                .add_option(CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "guilds",
+                    "list every guild the bot is in and its active modules",
+                ))
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "activate",
+                        "activate a module in a guild",
+                    )
+                    .add_sub_option(guild_option.clone())
+                    .add_sub_option(module_option.clone()),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "deactivate",
+                        "deactivate a module in a guild",
+                    )
+                    .add_sub_option(guild_option.clone())
+                    .add_sub_option(module_option.clone()),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "config",
+                        "show the config of a module in a guild",
+                    )
+                    .add_sub_option(guild_option)
+                    .add_sub_option(module_option),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "errors",
+                        "show the most recent module errors",
+                    )
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "module",
+                        "only errors of this module",
+                    ))
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "guild",
+                        "only errors of this guild id",
+                    )),
+                ),
+        )
+    }
+
+    /// the command follows the staff guild.
+    fn command_depends_on_config(&self) -> bool {
+        true
+    }
+
+    async fn command_handle(
+        &mut self,
+        ctx: &Context,
+        command: &CommandInteraction,
+    ) -> Result<(), ModuleError> {
+        if command.guild_id != Self::staff_guild().await? {
+            Err(BotAdminError::NotStaffGuild)?;
+        }
+        if !assert_bot_owner(ctx, command).await? {
+            return Ok(());
+        }
+
+        let Some(subcommand) = command.data.options.first() else {
+            return Ok(());
+        };
+        let CommandDataOptionValue::SubCommand(data) = &subcommand.value else {
+            return Ok(());
+        };
+        let option = |name: &str| {
+            data.iter()
+                .find(|option| option.name == name)
+                .and_then(|option| option.value.as_str())
+        };
+        let guild = option("guild").map(|guild| parse_guild(ctx, guild));
+        let module = option("module");
+
+        match (subcommand.name.as_str(), guild, module) {
+            ("guilds", _, _) => self.guilds_command(ctx, command).await?,
+            ("activate", Some(guild), Some(module)) => {
+                let guild = guild?;
+                get_module_mut::<ModuleManager>()?
+                    .module_mut::<ModuleManager>()
+                    .set_module_active(guild, module)
+                    .await?;
+                ModuleEventHandler::register_guild_module_command(
+                    ctx,
+                    guild,
+                    get_module_by_id(module)?,
+                )
+                .await;
+                respond(
+                    ctx,
+                    command,
+                    format!("Module `{module}` activated in `{guild}`."),
+                )
+                .await;
+            }
+            ("deactivate", Some(guild), Some(module)) => {
+                let guild = guild?;
+                get_module_mut::<ModuleManager>()?
+                    .module_mut::<ModuleManager>()
+                    .set_module_inactive(guild, module)
+                    .await?;
+                ModuleEventHandler::drop_guild_module_command(
+                    ctx,
+                    guild,
+                    get_module_by_id(module)?,
+                )
+                .await;
+                respond(
+                    ctx,
+                    command,
+                    format!("Module `{module}` deactivated in `{guild}`."),
+                )
+                .await;
+            }
+            ("config", Some(guild), Some(module)) => {
+                self.config_command(ctx, command, guild?, module).await?
+            }
+            ("errors", guild, module) => {
+                let guild = guild.transpose()?;
+                let error_manager = get_module::<ErrorManager>()?;
+                let mut description = String::new();
+                for record in error_manager
+                    .module::<ErrorManager>()
+                    .recent_errors(guild, module)
+                    .await
+                    .into_iter()
+                    .take(RECENT_ERRORS)
+                {
+                    let line = format!(
+                        "`#{}` {}: {}\n",
+                        record.id,
+                        record
+                            .guild
+                            .map(|guild| format!("`{guild}`"))
+                            .unwrap_or_else(|| "no guild".to_string()),
+                        record.summary()
+                    );
+                    if description.len() + line.len() > EMBED_DESCRIPTION_MAX_LEN {
+                        break;
+                    }
+                    description.push_str(&line);
+                }
+                if description.is_empty() {
+                    description.push_str("No errors recorded.");
+                }
+                send_embed(
+                    ctx,
+                    command,
+                    CreateEmbed::new()
+                        .title(format!(
+                            "Recent errors of {}",
+                            module.unwrap_or("all modules")
+                        ))
+                        .description(description),
+                )
+                .await;
+            }
+            (e, _, _) => warn!("unknown bot admin subcommand: {e}"),
+        }
+        Ok(())
+    }
+}
+
+impl BotAdmin {
+    async fn guilds_command(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+    ) -> Result<(), ModuleError> {
+        let module_manager = get_module::<ModuleManager>()?;
+        let module_manager = module_manager.module::<ModuleManager>();
+
+        let guilds = get_all_guilds(ctx).await?;
+        let mut description = String::new();
+        for (shown, guild) in guilds.iter().enumerate() {
+            let active = module_manager.get_all_active_module_ids(guild.id).await?;
+            let line = format!(
+                "`{}` {}: {}\n",
+                guild.id,
+                guild.name,
+                if active.is_empty() {
+                    "no active modules".to_string()
+                } else {
+                    active.join(", ")
+                }
+            );
+            if description.len() + line.len() > EMBED_DESCRIPTION_MAX_LEN {
+                description.push_str(&format!("... and {} more", guilds.len() - shown));
+                break;
+            }
+            description.push_str(&line);
+        }
+
+        send_embed(
+            ctx,
+            command,
+            CreateEmbed::new()
+                .title(format!("{} guilds", guilds.len()))
+                .description(description),
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn config_command(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+        guild: GuildId,
+        module_id: &str,
+    ) -> Result<(), ModuleError> {
+        let module = get_module_by_id(module_id)?;
+        let config = module.get_config(guild).await?;
+        let mut fields: Vec<_> = module.get_config_fields().into_iter().collect();
+        fields.sort_by_key(|(name, _)| *name);
+
+        let mut lines = vec![];
+        for (name, field) in fields {
+            let value = field.display(config.get_config_entry(name).await?)?;
+            lines.push(format!("`{name}`: {value}"));
+        }
+        if lines.is_empty() {
+            lines.push("This module has no config fields.".to_string());
+        }
+
+        send_embed(
+            ctx,
+            command,
+            CreateEmbed::new()
+                .title(format!("{module_id} config of `{guild}`"))
+                .description(lines.join("\n")),
+        )
+        .await;
+        Ok(())
+    }
+}
+
+/// a guild id the bot is a member of.
+fn parse_guild(ctx: &Context, guild: &str) -> Result<GuildId, ModuleError> {
+    let guild = guild
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|guild| *guild != 0)
+        .map(GuildId::new)
+        .ok_or(BotAdminError::InvalidGuild)?;
+    if !ctx.cache.guilds().contains(&guild) {
+        Err(BotAdminError::InvalidGuild)?;
+    }
+    Ok(guild)
+}
+
+async fn respond(ctx: &Context, command: &CommandInteraction, content: String) {
+    if let Err(error) = command
+        .create_followup(
+            ctx.http(),
+            CreateInteractionResponseFollowup::new().content(content),
+        )
+        .await
+    {
+        warn!("failed to create followup: {error:?}");
+    }
+}
+
+async fn send_embed(ctx: &Context, command: &CommandInteraction, embed: CreateEmbed) {
+    if let Err(error) = command
+        .create_followup(
+            ctx.http(),
+            CreateInteractionResponseFollowup::new().embed(embed),
+        )
+        .await
+    {
+        warn!("failed to create followup: {error:?}");
+    }
+}
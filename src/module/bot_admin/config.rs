@@ -0,0 +1,51 @@
+use super::BotAdmin;
+use crate::module::config::{
+    DragonModuleConfigurable, ModuleConfig,
+    entry::{ConfigEntryType, ConfigField, ConfigFieldError, ConfigValue},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct BotAdminConfig {
+    pub staff_guild: u64,
+}
+
+impl ModuleConfig for BotAdminConfig {
+    fn get_config_fields() -> HashMap<&'static str, ConfigField> {
+        [(
+            "staff_guild",
+            ConfigField::new(
+                ConfigEntryType::Guild,
+                "id of the guild the bot owner commands are registered in",
+            )
+            .global_only(),
+        )]
+        .into_iter()
+        .collect()
+    }
+
+    fn set_config_entry(
+        &mut self,
+        field: &str,
+        value: ConfigValue,
+    ) -> Result<(), ConfigFieldError> {
+        match field {
+            "staff_guild" => self.staff_guild = value.to_u64()?,
+            _ => return Err(ConfigFieldError::FieldNotFound),
+        }
+        Ok(())
+    }
+
+    fn get_config_entry(&self, field: &str) -> Result<ConfigValue, ConfigFieldError> {
+        match field {
+            "staff_guild" => Ok(ConfigValue::U64(self.staff_guild)),
+            _ => Err(ConfigFieldError::FieldNotFound),
+        }
+    }
+}
+
+impl DragonModuleConfigurable for BotAdmin {
+    type Config = BotAdminConfig;
+    type Module = BotAdmin;
+}
@@ -0,0 +1,58 @@
+use super::{
+    config::DragonModuleConfigurable,
+    errors::{ModuleError, UserFacingError},
+};
+use crate::core::{module::DragonBotModule, permissions::DragonModulePermission};
+use serenity::all::GuildId;
+use std::fmt::{self, Display, Formatter};
+
+mod command;
+pub mod config;
+
+#[derive(Debug)]
+pub enum BotAdminError {
+    InvalidGuild,
+    NotStaffGuild,
+}
+
+impl Display for BotAdminError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BotAdminError::InvalidGuild => {
+                write!(f, "That is not the id of a guild the bot is in.")
+            }
+            BotAdminError::NotStaffGuild => {
+                write!(f, "This command can only be used in the staff guild.")
+            }
+        }
+    }
+}
+
+impl UserFacingError for BotAdminError {
+    fn is_user_error(&self) -> bool {
+        true
+    }
+}
+
+/// owner-only commands acting on any guild, registered in the staff guild only.
+#[derive(Default)]
+pub struct BotAdmin;
+
+impl DragonBotModule for BotAdmin {
+    fn module_id() -> &'static str
+    where
+        Self: Sized,
+    {
+        "bot-admin"
+    }
+}
+
+impl DragonModulePermission for BotAdmin {}
+
+impl BotAdmin {
+    pub async fn staff_guild() -> Result<Option<GuildId>, ModuleError> {
+        Ok(Some(Self::get_global_config().await?.staff_guild)
+            .filter(|guild| *guild != 0)
+            .map(GuildId::new))
+    }
+}
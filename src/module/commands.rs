@@ -1,4 +1,33 @@
+use super::errors::UserFacingError;
+use std::fmt::{self, Display, Formatter};
+
 #[derive(Debug)]
 pub enum CommandError {
-    Serenity(serenity::Error),
+    Serenity(Box<serenity::Error>),
+    /// the options do not match the registered command, such as from an outdated command.
+    InvalidOptions,
+}
+
+impl From<serenity::Error> for CommandError {
+    fn from(value: serenity::Error) -> Self {
+        Self::Serenity(Box::new(value))
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Serenity(err) => write!(f, "discord error: {err}"),
+            CommandError::InvalidOptions => write!(
+                f,
+                "That command does not match its current version, try again in a moment."
+            ),
+        }
+    }
+}
+
+impl UserFacingError for CommandError {
+    fn is_user_error(&self) -> bool {
+        matches!(self, CommandError::InvalidOptions)
+    }
 }
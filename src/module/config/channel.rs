@@ -0,0 +1,98 @@
+use super::{ConfigError, entry::ConfigValue};
+use crate::{module::errors::ModuleError, util::config_path};
+use serde::{Deserialize, Serialize};
+use serenity::all::{Channel, ChannelId, Context, GuildId};
+use std::{collections::HashMap, path::PathBuf};
+use tokio::fs::{create_dir_all, read_to_string, write};
+
+const CHANNEL_CHAIN_MAX_DEPTH: usize = 3;
+
+/// per-channel values of channel overridable fields, keyed by channel or category id.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ChannelOverrides {
+    pub channels: HashMap<u64, HashMap<String, ConfigValue>>,
+}
+
+pub enum ValueSource {
+    Channel(ChannelId),
+    Guild,
+    Global,
+    Default,
+}
+
+impl ChannelOverrides {
+    pub fn get(&self, channel: ChannelId, field: &str) -> Option<&ConfigValue> {
+        self.channels.get(&channel.get())?.get(field)
+    }
+
+    pub fn set(&mut self, channel: ChannelId, field: &str, value: ConfigValue) {
+        self.channels
+            .entry(channel.get())
+            .or_default()
+            .insert(field.to_string(), value);
+    }
+
+    pub fn remove(&mut self, channel: ChannelId, field: &str) -> Option<ConfigValue> {
+        let fields = self.channels.get_mut(&channel.get())?;
+        let removed = fields.remove(field);
+        if fields.is_empty() {
+            self.channels.remove(&channel.get());
+        }
+        removed
+    }
+
+    /// the first override along the channel chain, closest channel first.
+    pub fn resolve(&self, chain: &[ChannelId], field: &str) -> Option<(ChannelId, &ConfigValue)> {
+        chain
+            .iter()
+            .find_map(|channel| self.get(*channel, field).map(|value| (*channel, value)))
+    }
+}
+
+/// the channel followed by its parents, e.g. thread, channel, category.
+pub async fn channel_chain(ctx: &Context, channel: ChannelId) -> Vec<ChannelId> {
+    let mut chain = vec![channel];
+    while chain.len() < CHANNEL_CHAIN_MAX_DEPTH {
+        let parent = match chain.last().unwrap().to_channel(ctx).await {
+            Ok(Channel::Guild(channel)) => channel.parent_id,
+            _ => None,
+        };
+        match parent {
+            Some(parent) => chain.push(parent),
+            None => break,
+        }
+    }
+    chain
+}
+
+async fn overrides_file(guild: GuildId, module: &str) -> Result<PathBuf, ModuleError> {
+    let path = config_path(&guild).await?.join("channels");
+    if !path.exists() {
+        create_dir_all(&path).await.map_err(ConfigError::IoError)?;
+    }
+    Ok(path.join(format!("{module}.json")))
+}
+
+pub async fn read_channel_overrides(
+    guild: GuildId,
+    module: &str,
+) -> Result<ChannelOverrides, ModuleError> {
+    let path = overrides_file(guild, module).await?;
+    if !path.exists() {
+        return Ok(ChannelOverrides::default());
+    }
+    let json = read_to_string(path).await.map_err(ConfigError::IoError)?;
+    Ok(serde_json::from_str(&json).map_err(ConfigError::SerdeError)?)
+}
+
+pub async fn write_channel_overrides(
+    guild: GuildId,
+    module: &str,
+    overrides: &ChannelOverrides,
+) -> Result<(), ModuleError> {
+    let json = serde_json::to_string(overrides).map_err(ConfigError::SerdeError)?;
+    write(overrides_file(guild, module).await?, json)
+        .await
+        .map_err(ConfigError::IoError)?;
+    Ok(())
+}
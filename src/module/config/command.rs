@@ -1,82 +1,230 @@
 use super::{
-    ConfigManager,
-    entry::{ConfigEntryType, ConfigValue},
+    ConfigError, ConfigManager,
+    channel::{
+        ChannelOverrides, ValueSource, channel_chain, read_channel_overrides,
+        write_channel_overrides,
+    },
+    entry::{ConfigEntryType, ConfigField, ConfigFieldError, ConfigValue},
+    history::{ConfigHistoryEntry, FieldChange, history_entry, module_history, record_history},
+    permission::{EDIT_CONFIG, VIEW_CONFIG},
+    secret::secret_key_available,
 };
 use crate::{
     core::{
         commands::DragonModuleCommand,
-        module::{DragonBotModule, get_module, get_module_by_id},
+        event_handler::ModuleEventHandler,
+        module::{DragonBotModule, get_module, get_module_by_id, get_module_by_id_mut},
+        modules::{DragonBotModuleInstance, ModuleConfigHolder},
+        permissions::{ModulePermission, assert_bot_owner, assert_permission, check_permission},
+    },
+    module::{
+        commands::CommandError,
+        errors::{ErrorManager, ErrorOrigin, ModuleError},
+        module_manager::{ModuleManager, ModuleManagerError},
     },
-    module::{errors::ModuleError, module_manager::ModuleManager},
 };
-use core::panic;
-use log::{debug, warn};
+use log::{debug, error, info, warn};
 use serenity::all::{
-    CacheHttp, ChannelType, CommandInteraction, CommandOptionType, Context, CreateCommand,
-    CreateCommandOption, EditInteractionResponse, GuildId, ResolvedValue,
+    Attachment, ButtonStyle, CacheHttp, ChannelId, CommandInteraction, CommandOptionType, Context,
+    CreateActionRow, CreateAllowedMentions, CreateAttachment, CreateButton, CreateCommand,
+    CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseFollowup, EditInteractionResponse, GuildId, ResolvedValue,
 };
+use std::{collections::HashSet, time::Duration};
+
+const EMBED_MAX_FIELDS: usize = 25;
+/// discord's limit of options per command and of subcommands per group.
+const COMMAND_MAX_OPTIONS: usize = 25;
+/// subcommands every module group has, fields cannot use their names.
+const MODULE_SUBCOMMANDS: [&str; 2] = ["view", "reset"];
+/// leaves room for `history`, `rollback`, `export` and `import`.
+const MAX_MODULE_GROUPS: usize = COMMAND_MAX_OPTIONS - 4;
+const MAX_MODULE_FIELDS: usize = COMMAND_MAX_OPTIONS - MODULE_SUBCOMMANDS.len();
+const HISTORY_PAGE_SIZE: usize = 10;
+const IMPORT_MAX_SIZE: u32 = 1024 * 1024;
+const IMPORT_PREVIEW_MAX_LEN: usize = 1500;
+const IMPORT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(120);
+const IMPORT_APPLY: &str = "config-import-apply";
+const IMPORT_CANCEL: &str = "config-import-cancel";
 
 impl DragonModuleCommand for ConfigManager {
     async fn command_builder(&self, guild: GuildId) -> Option<CreateCommand> {
         let mut toplevel = CreateCommand::new(ConfigManager::module_id())
             .description("manage user facing config settings for active modules.");
 
-        let module_manager =
-            get_module::<ModuleManager>().expect("failed to get module manager for reading");
+        let module_manager = get_module::<ModuleManager>()
+            .inspect_err(|err| warn!("failed to get module manager for reading: {err:?}"))
+            .ok()?;
         let module: &ModuleManager = module_manager.module();
-        let active = module
-            .get_all_active_module_ids(guild)
+        let mut active: Vec<String> = module
+            .get_command_module_ids(guild)
             .await
-            .expect("failed to get active modules")
-            .clone();
+            .inspect_err(|err| warn!("failed to get active modules of {guild}: {err:?}"))
+            .ok()?
+            .into_iter()
+            .filter(|id| id != ModuleManager::module_id())
+            .collect();
+        if active.len() > MAX_MODULE_GROUPS {
+            warn!(
+                "{guild} has more active modules than fit in the config command, leaving out {:?}",
+                &active[MAX_MODULE_GROUPS..]
+            );
+            active.truncate(MAX_MODULE_GROUPS);
+        }
 
-        for module in active
-            .iter()
-            .filter(|id| *id != "module-manager")
-            .map(|id| get_module_by_id(id))
-        {
-            let module = module.unwrap();
+        for module_id in &active {
+            let Ok(module) = get_module_by_id(module_id) else {
+                warn!("skipping invalid module {module_id}!");
+                continue;
+            };
             let mut module_subcommand = CreateCommandOption::new(
                 CommandOptionType::SubCommandGroup,
                 module.module_id(),
                 format!("config for {}", module.module_id()),
             );
 
-            for (field, field_data) in module.get_config_fields() {
+            let mut fields = module.get_config_fields();
+            fields.retain(|field, _| {
+                let reserved = MODULE_SUBCOMMANDS.contains(field);
+                if reserved {
+                    error!(
+                        "{} config field `{field}` is named like a built-in subcommand, leaving it out",
+                        module.module_id()
+                    );
+                }
+                !reserved
+            });
+            let mut field_names: Vec<&str> = fields.keys().copied().collect();
+            field_names.sort();
+            if field_names.len() > MAX_MODULE_FIELDS {
+                error!(
+                    "{} has more config fields than fit in the config command, leaving out {:?}",
+                    module.module_id(),
+                    &field_names[MAX_MODULE_FIELDS..]
+                );
+                field_names.truncate(MAX_MODULE_FIELDS);
+            }
+            let mut view_option = CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "view",
+                "view every config field of the module",
+            );
+            let mut reset_option = CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "reset",
+                "reset a field, or every field of the module, to its default",
+            )
+            .add_sub_option(field_names.iter().fold(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "field",
+                    "the field to reset, leave empty to reset the whole module",
+                ),
+                |option, field| option.add_string_choice(*field, *field),
+            ));
+            if fields.values().any(|field| field.channel_overridable) {
+                view_option = view_option.add_sub_option(channel_option());
+                reset_option = reset_option.add_sub_option(channel_option());
+            }
+            view_option = view_option.add_sub_option(global_option());
+            reset_option = reset_option.add_sub_option(global_option());
+            module_subcommand = module_subcommand
+                .add_sub_option(view_option)
+                .add_sub_option(reset_option);
+
+            for field in field_names {
+                let field_data = &fields[field];
                 let mut field_option = CreateCommandOption::new(
                     CommandOptionType::SubCommand,
                     field,
-                    field_data.description,
+                    &field_data.description,
                 );
-                field_option = field_option.add_sub_option(match field_data.field_type {
-                    ConfigEntryType::Role => {
-                        CreateCommandOption::new(CommandOptionType::Role, "value", "role value")
-                    }
-                    ConfigEntryType::User => {
-                        CreateCommandOption::new(CommandOptionType::User, "value", "user value")
-                    }
-                    ConfigEntryType::U64 => {
-                        CreateCommandOption::new(CommandOptionType::Number, "value", "u64 value")
-                    }
-                    ConfigEntryType::ChannelText => CreateCommandOption::new(
-                        CommandOptionType::Channel,
-                        "value",
-                        "channel value",
-                    )
-                    .channel_types(vec![ChannelType::Text]),
-                    ConfigEntryType::String => {
-                        CreateCommandOption::new(CommandOptionType::String, "value", "string value")
-                    }
-                });
+                field_option = field_option.add_sub_option(value_option(&field_data.field_type));
+                if field_data.channel_overridable {
+                    field_option = field_option.add_sub_option(channel_option());
+                }
+                field_option = field_option.add_sub_option(global_option());
                 module_subcommand = module_subcommand.add_sub_option(field_option);
             }
 
             toplevel = toplevel.add_option(module_subcommand);
         }
 
+        toplevel = toplevel
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "history",
+                    "browse the config change history of a module",
+                )
+                .add_sub_option(
+                    active.iter().fold(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "module",
+                            "the module to show history for",
+                        )
+                        .required(true),
+                        |option, id| option.add_string_choice(id, id),
+                    ),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "page", "the page")
+                        .min_int_value(1),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "rollback",
+                    "restore the value a field had before a history entry",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "entry",
+                        "the history entry id",
+                    )
+                    .min_int_value(1)
+                    .required(true),
+                ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "export",
+                "export the config of every active module as a file",
+            ))
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "import",
+                    "import a config file created by export",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Attachment,
+                        "file",
+                        "the exported config file",
+                    )
+                    .required(true),
+                ),
+            );
+
         Some(toplevel)
     }
 
+    /// the base requirements, handlers also check the permissions of the module and field.
+    fn subcommand_permissions(&self) -> Vec<(&'static str, Option<ModulePermission>)> {
+        vec![
+            ("history", Some(VIEW_CONFIG)),
+            ("rollback", Some(EDIT_CONFIG)),
+            ("export", Some(VIEW_CONFIG)),
+            ("import", Some(EDIT_CONFIG)),
+            ("* view", Some(VIEW_CONFIG)),
+            ("* reset", Some(EDIT_CONFIG)),
+        ]
+    }
+
     async fn command_handle(
         &mut self,
         ctx: &Context,
@@ -84,94 +232,172 @@ impl DragonModuleCommand for ConfigManager {
     ) -> Result<(), ModuleError> {
         let data = interaction.data.options();
 
-        let module_subcommand = data.first().expect("failed to get module id");
+        let module_subcommand = data.first().ok_or(CommandError::InvalidOptions)?;
+        match (module_subcommand.name, &module_subcommand.value) {
+            ("history", ResolvedValue::SubCommand(options)) => {
+                let mut module = "";
+                let mut page = 1;
+                for option in options {
+                    match (option.name, &option.value) {
+                        ("module", ResolvedValue::String(value)) => module = value,
+                        ("page", ResolvedValue::Integer(value)) => page = *value as usize,
+                        _ => Err(CommandError::InvalidOptions)?,
+                    }
+                }
+                return self.history_command(ctx, interaction, module, page).await;
+            }
+            ("rollback", ResolvedValue::SubCommand(options)) => {
+                let entry = match options.first().map(|option| &option.value) {
+                    Some(ResolvedValue::Integer(entry)) => *entry as u64,
+                    _ => Err(CommandError::InvalidOptions)?,
+                };
+                return self.rollback_command(ctx, interaction, entry).await;
+            }
+            ("export", _) => return self.export_command(ctx, interaction).await,
+            ("import", ResolvedValue::SubCommand(options)) => {
+                let file = match options.first().map(|option| &option.value) {
+                    Some(ResolvedValue::Attachment(file)) => file,
+                    _ => Err(CommandError::InvalidOptions)?,
+                };
+                return self.import_command(ctx, interaction, file).await;
+            }
+            _ => {}
+        }
+
+        let guild = interaction.guild_id.unwrap_or_default();
+        if !get_module::<ModuleManager>()?
+            .module::<ModuleManager>()
+            .is_module_id_active(guild, module_subcommand.name)
+            .await?
+        {
+            Err(ModuleManagerError::ModuleNotActive)?;
+        }
         let module = get_module_by_id(module_subcommand.name)?;
-        let mut module_config = module
-            .get_config(interaction.guild_id.unwrap_or_default())
-            .await?;
+        let mut module_config = module.get_config(guild).await?;
 
         let field = match &module_subcommand.value {
-            ResolvedValue::SubCommandGroup(data) => data.first().unwrap(),
-            _ => panic!(),
+            ResolvedValue::SubCommandGroup(data) => {
+                data.first().ok_or(CommandError::InvalidOptions)?
+            }
+            _ => Err(CommandError::InvalidOptions)?,
+        };
+        let options = match &field.value {
+            ResolvedValue::SubCommand(options) => options,
+            _ => Err(CommandError::InvalidOptions)?,
         };
+        let mut field_data = None;
+        let mut target = None;
+        let mut channel = None;
+        let mut global = false;
+        for option in options {
+            match (option.name, &option.value) {
+                ("value", value) => field_data = Some(value),
+                ("field", ResolvedValue::String(value)) => target = Some(*value),
+                ("channel", ResolvedValue::Channel(value)) => channel = Some(value.id),
+                ("global", ResolvedValue::Boolean(value)) => global = *value,
+                _ => Err(CommandError::InvalidOptions)?,
+            }
+        }
+
+        if global {
+            if channel.is_some() {
+                if let Err(err) = interaction
+                    .edit_response(
+                        ctx.http(),
+                        EditInteractionResponse::new()
+                            .content("Bot-wide values cannot be set per channel."),
+                    )
+                    .await
+                {
+                    warn!("failed to edit response: {err:?}");
+                }
+                return Ok(());
+            }
+            return self
+                .global_command(ctx, interaction, module, field.name, target, field_data)
+                .await;
+        }
+
+        match field.name {
+            "view" => return self.view_command(ctx, interaction, module, channel).await,
+            "reset" => {
+                return self
+                    .reset_command(ctx, interaction, module, module_config, target, channel)
+                    .await;
+            }
+            _ => {}
+        }
+
         let fields = module.get_config_fields();
         let field_prototype = fields
             .get(field.name)
-            .expect("failed to find field prototype");
+            .ok_or(ConfigFieldError::FieldNotFound)?;
 
-        let field_data = match &field.value {
-            ResolvedValue::SubCommand(field_data) => field_data.first(),
-            _ => panic!("invalid value for field command"),
-        };
+        let permissions = config_permissions(module, Some(field_prototype), field_data.is_some());
+        if !assert_permissions(ctx, interaction, permissions).await? {
+            return Ok(());
+        }
 
-        if field_data.is_none() {
-            debug!("getting {}", field.name);
-            let current = module_config
-                .get_config_entry(field.name)
-                .await
-                .expect("failed to get field");
+        if field_prototype.global_only && field_data.is_some() {
             if let Err(err) = interaction
                 .edit_response(
                     ctx.http(),
-                    EditInteractionResponse::new().content(format!(
-                        "Current value: {}",
-                        match field_prototype.field_type {
-                            ConfigEntryType::User => format!("<@{}>", current.to_u64().unwrap()),
-                            ConfigEntryType::Role => format!("<@&{}>", current.to_u64().unwrap()),
-                            ConfigEntryType::ChannelText =>
-                                format!("<#{}>", current.to_u64().unwrap()),
-                            ConfigEntryType::U64 => current.to_u64().unwrap().to_string(),
-                            ConfigEntryType::String => current.to_string().unwrap(),
-                        }
-                    )),
+                    EditInteractionResponse::new()
+                        .content(format!("`{}` can only be set bot-wide.", field.name)),
                 )
                 .await
             {
                 warn!("failed to edit response: {err:?}");
             }
-        } else {
-            debug!("setting {}", field.name);
-            if let Err(err) = module_config
-                .set_config_entry(
-                    field.name,
-                    match field_prototype.field_type {
-                        ConfigEntryType::U64
-                        | ConfigEntryType::User
-                        | ConfigEntryType::Role
-                        | ConfigEntryType::ChannelText => {
-                            ConfigValue::U64(match field_data.unwrap().value {
-                                ResolvedValue::User(user, _) => user.id.get(),
-                                ResolvedValue::Role(role) => role.id.get(),
-                                ResolvedValue::Channel(channel) => channel.id.get(),
-                                _ => panic!(),
-                            })
-                        }
-                        ConfigEntryType::String => {
-                            ConfigValue::String(match field_data.unwrap().value {
-                                ResolvedValue::String(string) => string.to_string(),
-                                _ => panic!(),
-                            })
-                        }
+            return Ok(());
+        }
+
+        if let Some(channel) = channel {
+            return self
+                .channel_field_command(
+                    ctx,
+                    interaction,
+                    &module_config,
+                    ChannelField {
+                        name: field.name,
+                        prototype: field_prototype,
+                        channel,
+                        value: field_data,
                     },
                 )
-                .await
-            {
+                .await;
+        }
+
+        if let Some(field_data) = field_data {
+            debug!("setting {}", field.name);
+            if field_prototype.secret && !secret_key_available() {
+                Err(ConfigError::SecretKeyMissing)?;
+            }
+            let result = match resolve_value(&field_prototype.field_type, field_data) {
+                Ok(value) => module_config.set_config_entry(field.name, value).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
                 if let Err(err2) = interaction
                     .edit_response(
                         ctx.http(),
                         EditInteractionResponse::new()
-                            .content(format!("Failed to update config entry: {:?}", err)),
+                            .content(format!("Failed to update config entry: {err}")),
                     )
                     .await
                 {
                     warn!("failed to edit response: {err2:?}");
                 };
             } else {
-                if let Err(err2) = module_config
-                    .save(interaction.guild_id.unwrap_or_default())
+                let guild = interaction.guild_id.unwrap_or_default();
+                match module_config
+                    .save_fields(guild, interaction.user.id, &[field.name], &[])
                     .await
                 {
-                    warn!("failed to save config: {err2:?}");
+                    Ok(_) => {
+                        notify_config_changed(ctx, guild, module.module_id(), &[field.name]).await
+                    }
+                    Err(err2) => warn!("failed to save config: {err2:?}"),
                 }
                 if let Err(err2) = interaction
                     .edit_response(
@@ -183,8 +409,974 @@ impl DragonModuleCommand for ConfigManager {
                     warn!("failed to edit response: {err2:?}");
                 }
             }
+        } else {
+            debug!("getting {}", field.name);
+            let global_config = module.get_global_config().await?;
+            let inherited = module
+                .inherited_fields(interaction.guild_id.unwrap_or_default())
+                .await?;
+            let (current, source) = effective_entry(
+                &module_config,
+                &global_config,
+                &inherited,
+                &ChannelOverrides::default(),
+                &[],
+                field.name,
+                field_prototype,
+            )
+            .await?;
+            if let Err(err) = interaction
+                .edit_response(
+                    ctx.http(),
+                    EditInteractionResponse::new().content(format!(
+                        "Current value: {} ({})",
+                        field_prototype.display(current)?,
+                        source_label(&source)
+                    )),
+                )
+                .await
+            {
+                warn!("failed to edit response: {err:?}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// a field read or set for one channel, `value` is unset when reading.
+struct ChannelField<'a> {
+    name: &'a str,
+    prototype: &'a ConfigField,
+    channel: ChannelId,
+    value: Option<&'a ResolvedValue<'a>>,
+}
+
+impl ConfigManager {
+    async fn channel_field_command(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        module_config: &ModuleConfigHolder,
+        ChannelField {
+            name: field,
+            prototype: field_prototype,
+            channel,
+            value,
+        }: ChannelField<'_>,
+    ) -> Result<(), ModuleError> {
+        let guild = interaction.guild_id.unwrap_or_default();
+        let module_id = module_config.module_id();
+
+        let response = if !field_prototype.channel_overridable || field_prototype.secret {
+            format!("`{field}` cannot be set per channel.")
+        } else if let Some(value) = value {
+            debug!("setting {field} for {channel}");
+            match resolve_value(&field_prototype.field_type, value) {
+                Ok(value) => {
+                    let mut overrides = read_channel_overrides(guild, module_id).await?;
+                    let old = overrides.get(channel, field).cloned();
+                    overrides.set(channel, field, value.clone());
+                    write_channel_overrides(guild, module_id, &overrides).await?;
+                    record_history(
+                        Some(guild),
+                        module_id,
+                        vec![FieldChange {
+                            field: field.to_string(),
+                            channel: Some(channel),
+                            old,
+                            new: Some(value),
+                            secret: false,
+                        }],
+                        interaction.user.id,
+                    )
+                    .await?;
+                    notify_config_changed(ctx, guild, module_id, &[field]).await;
+                    format!("Updated config entry for <#{channel}>.")
+                }
+                Err(err) => format!("Failed to update config entry: {err}"),
+            }
+        } else {
+            debug!("getting {field} for {channel}");
+            let overrides = read_channel_overrides(guild, module_id).await?;
+            let chain = channel_chain(ctx, channel).await;
+            let module = get_module_by_id(module_id)?;
+            let global_config = module.get_global_config().await?;
+            let inherited = module.inherited_fields(guild).await?;
+            let (value, source) = effective_entry(
+                module_config,
+                &global_config,
+                &inherited,
+                &overrides,
+                &chain,
+                field,
+                field_prototype,
+            )
+            .await?;
+            format!(
+                "Current value in <#{channel}>: {} ({})",
+                field_prototype.display(value)?,
+                source_label(&source)
+            )
+        };
+
+        if let Err(err) = interaction
+            .edit_response(ctx.http(), EditInteractionResponse::new().content(response))
+            .await
+        {
+            warn!("failed to edit response: {err:?}");
+        }
+        Ok(())
+    }
+
+    /// view and edit the bot-wide config of a module, bot owners only.
+    async fn global_command(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        module: &DragonBotModuleInstance,
+        field: &str,
+        target: Option<&str>,
+        value: Option<&ResolvedValue<'_>>,
+    ) -> Result<(), ModuleError> {
+        if !assert_bot_owner(ctx, interaction).await? {
+            return Ok(());
         }
 
+        let module_id = module.module_id();
+        let prototypes = module.get_config_fields();
+        let mut global_config = module.get_global_config().await?;
+        let mut response = EditInteractionResponse::new();
+        match field {
+            "view" => {
+                let mut fields: Vec<_> = prototypes.into_iter().collect();
+                fields.sort_by_key(|(name, _)| *name);
+                let mut embed = CreateEmbed::new().title(format!("{module_id} bot-wide config"));
+                if fields.is_empty() {
+                    embed = embed.description("This module has no config fields.");
+                }
+                for (name, field) in fields.into_iter().take(EMBED_MAX_FIELDS) {
+                    let value = match global_config.get_config_entry(name).await {
+                        Ok(value) => field
+                            .display(value)
+                            .unwrap_or_else(|err| format!("invalid: {err}")),
+                        Err(err) => format!("invalid: {err}"),
+                    };
+                    embed = embed.field(name, format!("{value}\n*{}*", field.description), false);
+                }
+                response = response.embed(embed);
+            }
+            "reset" => {
+                let fields: Vec<&str> = match target {
+                    Some(target) => vec![target],
+                    None => prototypes.keys().copied().collect(),
+                };
+                for field in &fields {
+                    global_config.reset_config_entry(field).await?;
+                }
+                global_config.save_global(interaction.user.id).await?;
+                info!(
+                    "{} reset bot-wide {fields:?} of {module_id}",
+                    interaction.user.id
+                );
+                let guilds = get_module::<ModuleManager>()?
+                    .module::<ModuleManager>()
+                    .active_guilds(ctx, module_id)
+                    .await;
+                for guild in guilds {
+                    notify_config_changed(ctx, guild, module_id, &fields).await;
+                }
+                response = response.content(match target {
+                    Some(target) => format!("Reset the bot-wide `{target}` to its default."),
+                    None => format!("Reset every bot-wide `{module_id}` field to its default."),
+                });
+            }
+            field => {
+                let prototype = prototypes
+                    .get(field)
+                    .ok_or(ConfigFieldError::FieldNotFound)?;
+                if let Some(value) = value {
+                    debug!("setting bot-wide {field}");
+                    if prototype.secret && !secret_key_available() {
+                        Err(ConfigError::SecretKeyMissing)?;
+                    }
+                    match resolve_value(&prototype.field_type, value) {
+                        Ok(value) => {
+                            global_config.set_config_entry(field, value).await?;
+                            global_config.save_global(interaction.user.id).await?;
+                            info!(
+                                "{} changed bot-wide {module_id}.{field}",
+                                interaction.user.id
+                            );
+                            let guilds = get_module::<ModuleManager>()?
+                                .module::<ModuleManager>()
+                                .active_guilds(ctx, module_id)
+                                .await;
+                            for guild in guilds {
+                                notify_config_changed(ctx, guild, module_id, &[field]).await;
+                            }
+                            response = response.content("Updated bot-wide config entry.");
+                        }
+                        Err(err) => {
+                            response =
+                                response.content(format!("Failed to update config entry: {err}"));
+                        }
+                    }
+                } else {
+                    debug!("getting bot-wide {field}");
+                    let current = global_config.get_config_entry(field).await?;
+                    response = response
+                        .content(format!("Bot-wide value: {}", prototype.display(current)?));
+                }
+            }
+        }
+
+        if let Err(err) = interaction.edit_response(ctx.http(), response).await {
+            warn!("failed to edit response: {err:?}");
+        }
         Ok(())
     }
+
+    async fn history_command(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        module_id: &str,
+        page: usize,
+    ) -> Result<(), ModuleError> {
+        let guild = interaction.guild_id.unwrap_or_default();
+        let module = get_module_by_id(module_id)?;
+        if !assert_permissions(ctx, interaction, config_permissions(module, None, false)).await? {
+            return Ok(());
+        }
+
+        let member = interaction
+            .member
+            .as_ref()
+            .ok_or(CommandError::InvalidOptions)?;
+        let mut hidden = HashSet::new();
+        let fields = module.get_config_fields();
+        for (name, field) in &fields {
+            if let Some(permission) = field.view_permission
+                && !check_permission(ctx, member, Some(interaction.channel_id), permission).await?
+            {
+                hidden.insert(*name);
+            }
+        }
+        let history = module_history(guild, module_id).await?;
+        let pages = history.len().div_ceil(HISTORY_PAGE_SIZE).max(1);
+        let page = page.min(pages);
+
+        let render = |entry: &ConfigHistoryEntry, value: &Option<ConfigValue>| match (
+            value,
+            fields.get(entry.field.as_str()),
+        ) {
+            _ if entry.secret => "[secret]".to_string(),
+            _ if hidden.contains(entry.field.as_str()) => "[hidden]".to_string(),
+            (None, _) => "-".to_string(),
+            (Some(value), Some(field)) => field
+                .display(value.clone())
+                .unwrap_or_else(|_| "?".to_string()),
+            (Some(value), None) => serde_json::to_string(value).unwrap_or_default(),
+        };
+
+        let mut description = String::new();
+        for entry in history
+            .iter()
+            .skip((page - 1) * HISTORY_PAGE_SIZE)
+            .take(HISTORY_PAGE_SIZE)
+        {
+            description.push_str(&format!(
+                "`#{}` <t:{}:R> <@{}> `{}`{}: {} → {}\n",
+                entry.id,
+                entry.timestamp,
+                entry.user,
+                entry.field,
+                entry
+                    .channel
+                    .map(|channel| format!(" in <#{channel}>"))
+                    .unwrap_or_default(),
+                render(entry, &entry.old),
+                render(entry, &entry.new),
+            ));
+        }
+        if description.is_empty() {
+            description.push_str("No changes recorded.");
+        }
+
+        let embed = CreateEmbed::new()
+            .title(format!("{module_id} config history"))
+            .description(description)
+            .footer(CreateEmbedFooter::new(format!("page {page}/{pages}")));
+        if let Err(err) = interaction
+            .edit_response(
+                ctx.http(),
+                EditInteractionResponse::new()
+                    .embed(embed)
+                    .allowed_mentions(CreateAllowedMentions::new()),
+            )
+            .await
+        {
+            warn!("failed to edit response: {err:?}");
+        }
+        Ok(())
+    }
+
+    async fn rollback_command(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        id: u64,
+    ) -> Result<(), ModuleError> {
+        let guild = interaction.guild_id.unwrap_or_default();
+        let response = match history_entry(guild, id).await? {
+            None => format!("History entry `#{id}` does not exist."),
+            Some(entry) if entry.secret => "Secret fields cannot be rolled back.".to_string(),
+            Some(entry) => {
+                let module = get_module_by_id(&entry.module)?;
+                let fields = module.get_config_fields();
+                let permissions =
+                    config_permissions(module, fields.get(entry.field.as_str()), true);
+                if !assert_permissions(ctx, interaction, permissions).await? {
+                    return Ok(());
+                }
+
+                if let Some(channel) = entry.channel {
+                    let mut overrides = read_channel_overrides(guild, &entry.module).await?;
+                    let current = match entry.old.clone() {
+                        Some(old) => {
+                            let current = overrides.get(channel, &entry.field).cloned();
+                            overrides.set(channel, &entry.field, old);
+                            current
+                        }
+                        None => overrides.remove(channel, &entry.field),
+                    };
+                    write_channel_overrides(guild, &entry.module, &overrides).await?;
+                    record_history(
+                        Some(guild),
+                        &entry.module,
+                        vec![FieldChange {
+                            field: entry.field.clone(),
+                            channel: Some(channel),
+                            old: current,
+                            new: entry.old,
+                            secret: false,
+                        }],
+                        interaction.user.id,
+                    )
+                    .await?;
+                    notify_config_changed(ctx, guild, &entry.module, &[&entry.field]).await;
+                    format!(
+                        "Rolled back `{}.{}` in <#{channel}> to its value before `#{id}`.",
+                        entry.module, entry.field
+                    )
+                } else {
+                    let mut module_config = module.get_config(guild).await?;
+                    let field = entry.field.as_str();
+                    let (result, set, inherit) = match entry.old {
+                        Some(old) => (
+                            module_config.set_config_entry(field, old).await,
+                            vec![field],
+                            vec![],
+                        ),
+                        None => (
+                            module_config.reset_config_entry(field).await,
+                            vec![],
+                            vec![field],
+                        ),
+                    };
+                    match result {
+                        Ok(_) => {
+                            module_config
+                                .save_fields(guild, interaction.user.id, &set, &inherit)
+                                .await?;
+                            notify_config_changed(ctx, guild, &entry.module, &[&entry.field]).await;
+                            format!(
+                                "Rolled back `{}.{}` to its value before `#{id}`.",
+                                entry.module, entry.field
+                            )
+                        }
+                        Err(err) => format!("Failed to roll back config entry: {err}"),
+                    }
+                }
+            }
+        };
+
+        if let Err(err) = interaction
+            .edit_response(ctx.http(), EditInteractionResponse::new().content(response))
+            .await
+        {
+            warn!("failed to edit response: {err:?}");
+        }
+        Ok(())
+    }
+
+    async fn view_command(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        module: &DragonBotModuleInstance,
+        channel: Option<ChannelId>,
+    ) -> Result<(), ModuleError> {
+        if !assert_permissions(ctx, interaction, config_permissions(module, None, false)).await? {
+            return Ok(());
+        }
+
+        let guild = interaction.guild_id.unwrap_or_default();
+        let member = interaction
+            .member
+            .as_ref()
+            .ok_or(CommandError::InvalidOptions)?;
+        let module_config = module.get_config(guild).await?;
+        let global_config = module.get_global_config().await?;
+        let inherited = module.inherited_fields(guild).await?;
+        let overrides = read_channel_overrides(guild, module.module_id()).await?;
+        let chain = match channel {
+            Some(channel) => channel_chain(ctx, channel).await,
+            None => vec![],
+        };
+
+        let mut fields: Vec<_> = module.get_config_fields().into_iter().collect();
+        fields.sort_by_key(|(name, _)| *name);
+
+        let mut embed = CreateEmbed::new().title(match channel {
+            Some(channel) => format!("{} config in <#{channel}>", module.module_id()),
+            None => format!("{} config", module.module_id()),
+        });
+        if fields.is_empty() {
+            embed = embed.description("This module has no config fields.");
+        }
+        for (name, field) in fields.into_iter().take(EMBED_MAX_FIELDS) {
+            if let Some(permission) = field.view_permission
+                && !check_permission(ctx, member, Some(interaction.channel_id), permission).await?
+            {
+                embed = embed.field(name, format!("[hidden]\n*{}*", field.description), false);
+                continue;
+            }
+            let value = match effective_entry(
+                &module_config,
+                &global_config,
+                &inherited,
+                &overrides,
+                &chain,
+                name,
+                &field,
+            )
+            .await
+            {
+                Ok((value, source)) => field
+                    .display(value)
+                    .map(|value| match source {
+                        ValueSource::Channel(_) | ValueSource::Global => {
+                            format!("{value} ({})", source_label(&source))
+                        }
+                        _ => value,
+                    })
+                    .unwrap_or_else(|err| format!("invalid: {err}")),
+                Err(err) => format!("invalid: {err}"),
+            };
+            embed = embed.field(name, format!("{value}\n*{}*", field.description), false);
+        }
+
+        if let Err(err) = interaction
+            .edit_response(ctx.http(), EditInteractionResponse::new().embed(embed))
+            .await
+        {
+            warn!("failed to edit response: {err:?}");
+        }
+        Ok(())
+    }
+
+    async fn reset_command(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        module: &DragonBotModuleInstance,
+        mut module_config: ModuleConfigHolder,
+        target: Option<&str>,
+        channel: Option<ChannelId>,
+    ) -> Result<(), ModuleError> {
+        let prototypes = module.get_config_fields();
+        let fields: Vec<&str> = match target {
+            Some(target) => vec![target],
+            None => prototypes.keys().copied().collect(),
+        };
+        let mut permissions = config_permissions(module, None, true);
+        for field in &fields {
+            permissions.extend(config_permissions(module, prototypes.get(field), true));
+        }
+        if !assert_permissions(ctx, interaction, permissions).await? {
+            return Ok(());
+        }
+
+        if let Some(channel) = channel {
+            let guild = interaction.guild_id.unwrap_or_default();
+            debug!(
+                "resetting {fields:?} of {} for {channel}",
+                module.module_id()
+            );
+            let mut overrides = read_channel_overrides(guild, module.module_id()).await?;
+            let mut changes = vec![];
+            for field in fields {
+                if let Some(old) = overrides.remove(channel, field) {
+                    changes.push(FieldChange {
+                        field: field.to_string(),
+                        channel: Some(channel),
+                        old: Some(old),
+                        new: None,
+                        secret: false,
+                    });
+                }
+            }
+            write_channel_overrides(guild, module.module_id(), &overrides).await?;
+            let changed: Vec<String> = changes.iter().map(|change| change.field.clone()).collect();
+            record_history(
+                Some(guild),
+                module.module_id(),
+                changes,
+                interaction.user.id,
+            )
+            .await?;
+            notify_config_changed(
+                ctx,
+                guild,
+                module.module_id(),
+                &changed.iter().map(String::as_str).collect::<Vec<_>>(),
+            )
+            .await;
+
+            if let Err(err) = interaction
+                .edit_response(
+                    ctx.http(),
+                    EditInteractionResponse::new()
+                        .content(format!("Removed the overrides for <#{channel}>.")),
+                )
+                .await
+            {
+                warn!("failed to edit response: {err:?}");
+            }
+            return Ok(());
+        }
+
+        debug!("resetting {fields:?} of {}", module.module_id());
+        for field in &fields {
+            if let Err(err) = module_config.reset_config_entry(field).await {
+                if let Err(err2) = interaction
+                    .edit_response(
+                        ctx.http(),
+                        EditInteractionResponse::new()
+                            .content(format!("Failed to reset config entry: {err}")),
+                    )
+                    .await
+                {
+                    warn!("failed to edit response: {err2:?}");
+                }
+                return Ok(());
+            }
+        }
+        let guild = interaction.guild_id.unwrap_or_default();
+        let changed: Vec<String> = module_config
+            .changes_from_disk(guild)
+            .await?
+            .into_iter()
+            .map(|change| change.field)
+            .collect();
+        module_config
+            .save_fields(guild, interaction.user.id, &[], &fields)
+            .await?;
+        notify_config_changed(
+            ctx,
+            guild,
+            module.module_id(),
+            &changed.iter().map(String::as_str).collect::<Vec<_>>(),
+        )
+        .await;
+
+        if let Err(err) = interaction
+            .edit_response(
+                ctx.http(),
+                EditInteractionResponse::new().content(match target {
+                    Some(target) => format!("Reset `{target}` to its default."),
+                    None => format!("Reset every `{}` field to its default.", module.module_id()),
+                }),
+            )
+            .await
+        {
+            warn!("failed to edit response: {err:?}");
+        }
+        Ok(())
+    }
+
+    async fn export_command(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), ModuleError> {
+        let guild = interaction.guild_id.unwrap_or_default();
+        let export = self.export_guild(guild).await?;
+
+        let mut permissions = vec![];
+        for module_id in export.modules.keys() {
+            let module = get_module_by_id(module_id)?;
+            permissions.extend(config_permissions(module, None, false));
+            for field in module.get_config_fields().values() {
+                permissions.extend(config_permissions(module, Some(field), false));
+            }
+        }
+        if !assert_permissions(ctx, interaction, permissions).await? {
+            return Ok(());
+        }
+        let json = serde_json::to_vec_pretty(&export).map_err(ConfigError::SerdeError)?;
+
+        if let Err(err) = interaction
+            .create_followup(
+                ctx.http(),
+                CreateInteractionResponseFollowup::new()
+                    .content("Exported config. Secret fields are not included.")
+                    .add_file(CreateAttachment::bytes(
+                        json,
+                        format!("config-{guild}.json"),
+                    ))
+                    .ephemeral(true),
+            )
+            .await
+        {
+            warn!("failed to send config export: {err:?}");
+        }
+        Ok(())
+    }
+
+    async fn import_command(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        file: &Attachment,
+    ) -> Result<(), ModuleError> {
+        let guild = interaction.guild_id.unwrap_or_default();
+        if file.size > IMPORT_MAX_SIZE {
+            Err(ConfigError::ImportInvalid("file is too large".to_string()))?;
+        }
+        let raw = file.download().await.map_err(CommandError::from)?;
+        let pending = self.prepare_import(guild, &raw).await?;
+
+        let mut permissions = vec![];
+        for change in &pending.changes {
+            let module = get_module_by_id(&change.module)?;
+            let fields = module.get_config_fields();
+            let field = change.field.as_deref().and_then(|field| fields.get(field));
+            permissions.extend(config_permissions(module, field, true));
+        }
+        if !assert_permissions(ctx, interaction, permissions).await? {
+            return Ok(());
+        }
+
+        if pending.changes.is_empty() {
+            if let Err(err) = interaction
+                .edit_response(
+                    ctx.http(),
+                    EditInteractionResponse::new().content("Import contains no changes."),
+                )
+                .await
+            {
+                warn!("failed to edit response: {err:?}");
+            }
+            return Ok(());
+        }
+
+        let mut preview = "```diff\n".to_string();
+        let mut shown = 0;
+        for change in &pending.changes {
+            let mut lines = String::new();
+            if let Some(old) = &change.old {
+                lines.push_str(&format!("- {}: {old}\n", change.path()));
+            }
+            if let Some(new) = &change.new {
+                lines.push_str(&format!("+ {}: {new}\n", change.path()));
+            }
+            if preview.len() + lines.len() > IMPORT_PREVIEW_MAX_LEN {
+                break;
+            }
+            preview.push_str(&lines);
+            shown += 1;
+        }
+        if shown < pending.changes.len() {
+            preview.push_str(&format!(
+                "... and {} more changes\n",
+                pending.changes.len() - shown
+            ));
+        }
+        preview.push_str("```");
+        if !pending.skipped_secrets.is_empty() {
+            preview.push_str(&format!(
+                "\nSecret fields are not imported, set them again with `/config-manager`: {}",
+                pending.skipped_secrets.join(", ")
+            ));
+        }
+
+        let message = interaction
+            .edit_response(
+                ctx.http(),
+                EditInteractionResponse::new()
+                    .content(format!("Apply this import?\n{preview}"))
+                    .components(vec![CreateActionRow::Buttons(vec![
+                        CreateButton::new(IMPORT_APPLY)
+                            .label("Apply")
+                            .style(ButtonStyle::Danger),
+                        CreateButton::new(IMPORT_CANCEL)
+                            .label("Cancel")
+                            .style(ButtonStyle::Secondary),
+                    ])]),
+            )
+            .await
+            .map_err(CommandError::from)?;
+
+        let press = message
+            .await_component_interaction(&ctx.shard)
+            .author_id(interaction.user.id)
+            .timeout(IMPORT_CONFIRM_TIMEOUT)
+            .await;
+
+        if let Some(press) = &press
+            && let Err(err) = press
+                .create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+                .await
+        {
+            warn!("failed to acknowledge button press: {err:?}");
+        }
+
+        let applied = press
+            .as_ref()
+            .is_some_and(|press| press.data.custom_id == IMPORT_APPLY);
+        let changed: Vec<(String, String)> = pending
+            .changes
+            .iter()
+            .filter_map(|change| Some((change.module.clone(), change.field.clone()?)))
+            .collect();
+        let result = if applied {
+            self.apply_import(guild, pending, interaction.user.id).await
+        } else {
+            Ok(())
+        };
+
+        let response = EditInteractionResponse::new()
+            .content(match (&press, &result) {
+                (_, Err(_)) => "Import failed.",
+                (Some(_), Ok(_)) if applied => "Config imported.",
+                (Some(_), Ok(_)) => "Import cancelled.",
+                (None, Ok(_)) => "Import timed out.",
+            })
+            .components(vec![]);
+        if let Err(err) = interaction.edit_response(ctx.http(), response).await {
+            warn!("failed to edit response: {err:?}");
+        }
+        result?;
+
+        if applied {
+            for (module, field) in &changed {
+                notify_config_changed(ctx, guild, module, &[field]).await;
+            }
+        }
+        if applied && let Err(err) = ModuleEventHandler::reset_guild_commands(ctx, guild).await {
+            warn!("failed to reset guild commands after import: {err:?}");
+        }
+        Ok(())
+    }
+}
+
+async fn effective_entry(
+    module_config: &ModuleConfigHolder,
+    global_config: &ModuleConfigHolder,
+    inherited: &HashSet<&str>,
+    overrides: &ChannelOverrides,
+    chain: &[ChannelId],
+    field: &str,
+    prototype: &ConfigField,
+) -> Result<(ConfigValue, ValueSource), ConfigFieldError> {
+    if let Some((channel, value)) = overrides.resolve(chain, field) {
+        return Ok((value.clone(), ValueSource::Channel(channel)));
+    }
+    let default = module_config.default_config_entry(field)?;
+    let value = module_config.get_config_entry(field).await?;
+    let global = global_config.get_config_entry(field).await?;
+    let (value, source) = if prototype.global_only || inherited.contains(field) {
+        (global, ValueSource::Global)
+    } else {
+        (value, ValueSource::Guild)
+    };
+    let source = if value == default {
+        ValueSource::Default
+    } else {
+        source
+    };
+    Ok((value, source))
+}
+
+fn source_label(source: &ValueSource) -> String {
+    match source {
+        ValueSource::Channel(channel) => format!("set on <#{channel}>"),
+        ValueSource::Guild => "guild value".to_string(),
+        ValueSource::Global => "bot-wide value".to_string(),
+        ValueSource::Default => "default".to_string(),
+    }
+}
+
+/// lets the module react to its changed fields, re-registering its command if that depends
+/// on config. the change is already saved, so failures are only logged.
+async fn notify_config_changed(ctx: &Context, guild: GuildId, module_id: &str, fields: &[&str]) {
+    let module = match get_module_by_id_mut(module_id) {
+        Ok(module) => module,
+        Err(err) => {
+            warn!("failed to get {module_id} to notify of config change: {err:?}");
+            return;
+        }
+    };
+    for field in fields {
+        if let Err(err) = module.on_config_changed(ctx, guild, field).await {
+            warn!("{module_id} failed to handle change of `{field}` in {guild}");
+            ErrorManager::report(ctx, ErrorOrigin::new(module_id).guild(guild), &err).await;
+        }
+    }
+    if !fields.is_empty() && module.command_depends_on_config() {
+        ModuleEventHandler::register_guild_module_command(ctx, guild, module).await;
+    }
+}
+
+fn channel_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::Channel,
+        "channel",
+        "the channel or category to use instead of the whole guild",
+    )
+}
+
+fn global_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::Boolean,
+        "global",
+        "use the bot-wide value shared by every guild, bot owners only",
+    )
+}
+
+fn config_permissions(
+    module: &DragonBotModuleInstance,
+    field: Option<&ConfigField>,
+    edit: bool,
+) -> Vec<ModulePermission> {
+    let (base, module_permission, field_permission) = if edit {
+        (
+            EDIT_CONFIG,
+            module.config_edit_permission(),
+            field.and_then(|field| field.edit_permission),
+        )
+    } else {
+        (
+            VIEW_CONFIG,
+            module.config_view_permission(),
+            field.and_then(|field| field.view_permission),
+        )
+    };
+    [Some(base), module_permission, field_permission]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+async fn assert_permissions(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    permissions: impl IntoIterator<Item = ModulePermission>,
+) -> Result<bool, ModuleError> {
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or(CommandError::InvalidOptions)?;
+    let mut checked: Vec<ModulePermission> = vec![];
+    for permission in permissions {
+        if checked.contains(&permission) {
+            continue;
+        }
+        if !assert_permission(ctx, interaction, member, permission).await? {
+            return Ok(false);
+        }
+        checked.push(permission);
+    }
+    Ok(true)
+}
+
+fn value_option(field_type: &ConfigEntryType) -> CreateCommandOption {
+    match field_type {
+        ConfigEntryType::Role => {
+            CreateCommandOption::new(CommandOptionType::Role, "value", "role value")
+        }
+        ConfigEntryType::User => {
+            CreateCommandOption::new(CommandOptionType::User, "value", "user value")
+        }
+        ConfigEntryType::U64 => {
+            CreateCommandOption::new(CommandOptionType::Integer, "value", "u64 value")
+                .min_int_value(0)
+        }
+        ConfigEntryType::I64 => {
+            CreateCommandOption::new(CommandOptionType::Integer, "value", "i64 value")
+        }
+        ConfigEntryType::Bool => {
+            CreateCommandOption::new(CommandOptionType::Boolean, "value", "bool value")
+        }
+        ConfigEntryType::Duration => CreateCommandOption::new(
+            CommandOptionType::String,
+            "value",
+            "duration value, e.g. 30m or 1h30m",
+        ),
+        ConfigEntryType::Enum(choices) => choices.iter().fold(
+            CreateCommandOption::new(CommandOptionType::String, "value", "choice value"),
+            |option, choice| option.add_string_choice(choice, choice),
+        ),
+        ConfigEntryType::Channel(channel_types) => {
+            CreateCommandOption::new(CommandOptionType::Channel, "value", "channel value")
+                .channel_types(channel_types.clone())
+        }
+        ConfigEntryType::Guild => {
+            CreateCommandOption::new(CommandOptionType::String, "value", "guild id")
+        }
+        ConfigEntryType::String => {
+            CreateCommandOption::new(CommandOptionType::String, "value", "string value")
+        }
+        ConfigEntryType::RoleList => CreateCommandOption::new(
+            CommandOptionType::String,
+            "value",
+            "space separated role mentions",
+        ),
+        ConfigEntryType::ChannelList(_) => CreateCommandOption::new(
+            CommandOptionType::String,
+            "value",
+            "space separated channel mentions",
+        ),
+        ConfigEntryType::StringList => {
+            CreateCommandOption::new(CommandOptionType::String, "value", "comma separated values")
+        }
+    }
+}
+
+fn resolve_value(
+    field_type: &ConfigEntryType,
+    value: &ResolvedValue,
+) -> Result<ConfigValue, ConfigFieldError> {
+    Ok(match (field_type, value) {
+        (ConfigEntryType::User, ResolvedValue::User(user, _)) => ConfigValue::U64(user.id.get()),
+        (ConfigEntryType::Role, ResolvedValue::Role(role)) => ConfigValue::U64(role.id.get()),
+        (ConfigEntryType::Channel(channel_types), ResolvedValue::Channel(channel)) => {
+            if !channel_types.is_empty() && !channel_types.contains(&channel.kind) {
+                return Err(ConfigFieldError::ValueWrongType);
+            }
+            ConfigValue::U64(channel.id.get())
+        }
+        (ConfigEntryType::U64, ResolvedValue::Integer(integer)) => ConfigValue::U64(
+            (*integer)
+                .try_into()
+                .map_err(|_| ConfigFieldError::MalformedData)?,
+        ),
+        (ConfigEntryType::I64, ResolvedValue::Integer(integer)) => ConfigValue::I64(*integer),
+        (ConfigEntryType::Bool, ResolvedValue::Boolean(boolean)) => ConfigValue::Bool(*boolean),
+        (_, ResolvedValue::String(string)) => field_type.parse_raw(string)?,
+        _ => return Err(ConfigFieldError::ValueWrongType),
+    })
 }
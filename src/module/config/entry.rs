@@ -1,6 +1,14 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    hash::Hash,
+    time::Duration,
+};
 
 use serde::{Deserialize, Serialize};
+use serenity::all::ChannelType;
+
+use crate::{core::permissions::ModulePermission, module::errors::UserFacingError};
 
 #[derive(Debug)]
 pub enum ConfigFieldError {
@@ -10,6 +18,25 @@ pub enum ConfigFieldError {
     NotInit,
 }
 
+impl Display for ConfigFieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigFieldError::FieldNotFound => write!(f, "That config field does not exist."),
+            ConfigFieldError::ValueWrongType => {
+                write!(f, "That value has the wrong type for the field.")
+            }
+            ConfigFieldError::MalformedData => write!(f, "That value is malformed."),
+            ConfigFieldError::NotInit => write!(f, "the config is not initialized"),
+        }
+    }
+}
+
+impl UserFacingError for ConfigFieldError {
+    fn is_user_error(&self) -> bool {
+        !matches!(self, ConfigFieldError::NotInit)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ConfigEntry {
     value_type: ConfigEntryType,
@@ -21,6 +48,12 @@ pub struct ConfigEntry {
 pub struct ConfigField {
     pub field_type: ConfigEntryType,
     pub description: String,
+    pub secret: bool,
+    pub channel_overridable: bool,
+    pub view_permission: Option<ModulePermission>,
+    pub edit_permission: Option<ModulePermission>,
+    pub global_only: bool,
+    pub inheritable: bool,
 }
 
 impl ConfigField {
@@ -28,28 +61,103 @@ impl ConfigField {
         Self {
             field_type,
             description: description.into(),
+            secret: false,
+            channel_overridable: false,
+            view_permission: None,
+            edit_permission: None,
+            global_only: false,
+            inheritable: false,
         }
     }
+
+    /// allows the field to be set per channel or category, see `get_effective_config`.
+    pub fn channel_overridable(mut self) -> Self {
+        self.channel_overridable = true;
+        self
+    }
+
+    /// only the bot-wide value is used, guilds cannot set their own.
+    pub fn global_only(mut self) -> Self {
+        self.global_only = true;
+        self
+    }
+
+    /// guilds that do not set the field use the bot-wide value, see `get_guild_config`. the
+    /// field must be serialized under its name.
+    pub fn inheritable(mut self) -> Self {
+        self.inheritable = true;
+        self
+    }
+
+    /// required to read the field, on top of the module's view permissions.
+    pub fn view_permission(mut self, permission: ModulePermission) -> Self {
+        self.view_permission = Some(permission);
+        self
+    }
+
+    /// required to change the field, on top of the module's edit permissions.
+    pub fn edit_permission(mut self, permission: ModulePermission) -> Self {
+        self.edit_permission = Some(permission);
+        self
+    }
+
+    /// marks the field as secret, its value is never displayed and is encrypted on disk.
+    pub fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+
+    /// renders the current value of the field, masking secrets.
+    pub fn display(&self, value: ConfigValue) -> Result<String, ConfigFieldError> {
+        if self.secret {
+            return Ok(if value.is_unset() { "unset" } else { "set" }.to_string());
+        }
+        self.field_type.display(value)
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub enum ConfigEntryType {
     String,
     U64,
+    I64,
+    Bool,
+    /// stored as whole seconds, entered as e.g. `30m` or `1h30m`
+    Duration,
+    /// a string restricted to the given choices
+    Enum(Vec<String>),
     Role,
     User,
-    ChannelText,
+    Channel(Vec<ChannelType>),
+    /// a guild id, entered as a string since discord has no guild option
+    Guild,
+    RoleList,
+    ChannelList(Vec<ChannelType>),
+    StringList,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub enum ConfigValue {
     String(String),
     U64(u64),
+    I64(i64),
+    Bool(bool),
     Vec(Box<Vec<ConfigValue>>),
     HashMap(Box<HashMap<String, ConfigValue>>),
 }
 
 impl ConfigValue {
+    pub fn is_unset(&self) -> bool {
+        match self {
+            ConfigValue::String(value) => value.is_empty(),
+            ConfigValue::U64(value) => *value == 0,
+            ConfigValue::I64(value) => *value == 0,
+            ConfigValue::Bool(_) => false,
+            ConfigValue::Vec(value) => value.is_empty(),
+            ConfigValue::HashMap(value) => value.is_empty(),
+        }
+    }
+
     pub fn to_string(self) -> Result<String, ConfigFieldError> {
         match self {
             ConfigValue::String(value) => Ok(value),
@@ -64,6 +172,24 @@ impl ConfigValue {
         }
     }
 
+    pub fn to_i64(self) -> Result<i64, ConfigFieldError> {
+        match self {
+            ConfigValue::I64(value) => Ok(value),
+            _ => Err(ConfigFieldError::ValueWrongType),
+        }
+    }
+
+    pub fn to_bool(self) -> Result<bool, ConfigFieldError> {
+        match self {
+            ConfigValue::Bool(value) => Ok(value),
+            _ => Err(ConfigFieldError::ValueWrongType),
+        }
+    }
+
+    pub fn to_duration(self) -> Result<Duration, ConfigFieldError> {
+        Ok(Duration::from_secs(self.to_u64()?))
+    }
+
     pub fn to_vec(self) -> Result<Vec<ConfigValue>, ConfigFieldError> {
         match self {
             ConfigValue::Vec(value) => Ok(*value),
@@ -71,6 +197,20 @@ impl ConfigValue {
         }
     }
 
+    pub fn to_u64_vec(self) -> Result<Vec<u64>, ConfigFieldError> {
+        self.to_vec()?
+            .into_iter()
+            .map(ConfigValue::to_u64)
+            .collect()
+    }
+
+    pub fn to_string_vec(self) -> Result<Vec<String>, ConfigFieldError> {
+        self.to_vec()?
+            .into_iter()
+            .map(ConfigValue::to_string)
+            .collect()
+    }
+
     pub fn to_map<T>(self) -> Result<HashMap<T, ConfigValue>, ConfigFieldError>
     where
         T: for<'de> Deserialize<'de> + Hash + Eq,
@@ -89,6 +229,168 @@ impl ConfigValue {
             _ => Err(ConfigFieldError::ValueWrongType),
         }
     }
+
+    pub fn from_u64_vec(values: impl IntoIterator<Item = u64>) -> Self {
+        ConfigValue::Vec(Box::new(values.into_iter().map(ConfigValue::U64).collect()))
+    }
+
+    pub fn from_string_vec(values: impl IntoIterator<Item = String>) -> Self {
+        ConfigValue::Vec(Box::new(
+            values.into_iter().map(ConfigValue::String).collect(),
+        ))
+    }
+}
+
+impl ConfigEntryType {
+    /// parses the user facing string form of a value, as stored in `ConfigEntry::raw`
+    /// or entered through a string command option.
+    pub fn parse_raw(&self, raw: &str) -> Result<ConfigValue, ConfigFieldError> {
+        let raw = raw.trim();
+        Ok(match self {
+            ConfigEntryType::String => ConfigValue::String(raw.to_string()),
+            ConfigEntryType::U64 => {
+                ConfigValue::U64(raw.parse().map_err(|_| ConfigFieldError::MalformedData)?)
+            }
+            ConfigEntryType::I64 => {
+                ConfigValue::I64(raw.parse().map_err(|_| ConfigFieldError::MalformedData)?)
+            }
+            ConfigEntryType::Bool => ConfigValue::Bool(match raw.to_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => true,
+                "false" | "no" | "off" | "0" => false,
+                _ => return Err(ConfigFieldError::MalformedData),
+            }),
+            ConfigEntryType::Duration => ConfigValue::U64(parse_duration(raw)?.as_secs()),
+            ConfigEntryType::Enum(choices) => {
+                if !choices.iter().any(|choice| choice == raw) {
+                    return Err(ConfigFieldError::MalformedData);
+                }
+                ConfigValue::String(raw.to_string())
+            }
+            ConfigEntryType::Role
+            | ConfigEntryType::User
+            | ConfigEntryType::Channel(_)
+            | ConfigEntryType::Guild => ConfigValue::U64(parse_snowflake(raw)?),
+            ConfigEntryType::RoleList | ConfigEntryType::ChannelList(_) => {
+                ConfigValue::from_u64_vec(
+                    split_list(raw)
+                        .map(parse_snowflake)
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            }
+            ConfigEntryType::StringList => {
+                ConfigValue::from_string_vec(split_list(raw).map(str::to_string))
+            }
+        })
+    }
+
+    /// renders a value of this type for display in discord.
+    pub fn display(&self, value: ConfigValue) -> Result<String, ConfigFieldError> {
+        Ok(match self {
+            ConfigEntryType::String | ConfigEntryType::Enum(_) => value.to_string()?,
+            ConfigEntryType::U64 => value.to_u64()?.to_string(),
+            ConfigEntryType::I64 => value.to_i64()?.to_string(),
+            ConfigEntryType::Bool => value.to_bool()?.to_string(),
+            ConfigEntryType::Duration => format_duration(value.to_duration()?),
+            ConfigEntryType::Role => format!("<@&{}>", value.to_u64()?),
+            ConfigEntryType::User => format!("<@{}>", value.to_u64()?),
+            ConfigEntryType::Channel(_) => format!("<#{}>", value.to_u64()?),
+            ConfigEntryType::Guild => format!("`{}`", value.to_u64()?),
+            ConfigEntryType::RoleList => join_or_none(
+                value
+                    .to_u64_vec()?
+                    .into_iter()
+                    .map(|id| format!("<@&{id}>")),
+            ),
+            ConfigEntryType::ChannelList(_) => {
+                join_or_none(value.to_u64_vec()?.into_iter().map(|id| format!("<#{id}>")))
+            }
+            ConfigEntryType::StringList => join_or_none(
+                value
+                    .to_string_vec()?
+                    .into_iter()
+                    .map(|string| format!("`{string}`")),
+            ),
+        })
+    }
+}
+
+fn split_list(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|item| !item.is_empty())
+}
+
+fn join_or_none(items: impl Iterator<Item = String>) -> String {
+    let items: Vec<String> = items.collect();
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// accepts a raw id or a user, role, or channel mention.
+fn parse_snowflake(raw: &str) -> Result<u64, ConfigFieldError> {
+    raw.trim_start_matches('<')
+        .trim_end_matches('>')
+        .trim_start_matches(['@', '#', '&', '!'])
+        .parse()
+        .map_err(|_| ConfigFieldError::MalformedData)
+}
+
+/// parses durations such as `90`, `30m`, or `1d2h30m15s`. bare numbers are seconds.
+pub fn parse_duration(raw: &str) -> Result<Duration, ConfigFieldError> {
+    if raw.is_empty() {
+        return Err(ConfigFieldError::MalformedData);
+    }
+    if let Ok(seconds) = raw.parse() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in raw.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let multiplier = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 60 * 60 * 24,
+            'w' => 60 * 60 * 24 * 7,
+            _ => return Err(ConfigFieldError::MalformedData),
+        };
+        let value: u64 = number
+            .parse()
+            .map_err(|_| ConfigFieldError::MalformedData)?;
+        total = value
+            .checked_mul(multiplier)
+            .and_then(|value| total.checked_add(value))
+            .ok_or(ConfigFieldError::MalformedData)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(ConfigFieldError::MalformedData);
+    }
+
+    Ok(Duration::from_secs(total))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let mut seconds = duration.as_secs();
+    if seconds == 0 {
+        return "0s".to_string();
+    }
+
+    let mut formatted = String::new();
+    for (unit, size) in [("d", 60 * 60 * 24), ("h", 60 * 60), ("m", 60), ("s", 1)] {
+        if seconds >= size {
+            formatted.push_str(&format!("{}{unit}", seconds / size));
+            seconds %= size;
+        }
+    }
+    formatted
 }
 
 impl ConfigEntry {
@@ -104,19 +406,135 @@ impl ConfigEntry {
         if self.data.is_some() {
             return Ok(());
         }
-        match self.value_type {
-            ConfigEntryType::String => self.data = Some(ConfigValue::String(self.raw.clone())),
-            ConfigEntryType::U64
-            | ConfigEntryType::Role
-            | ConfigEntryType::User
-            | ConfigEntryType::ChannelText => {
-                self.data = Some(ConfigValue::U64(
-                    self.raw
-                        .parse()
-                        .map_err(|_| ConfigFieldError::MalformedData)?,
-                ))
-            }
-        }
+        self.data = Some(self.value_type.parse_raw(&self.raw)?);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(30 * 60));
+        assert_eq!(
+            parse_duration("1d2h30m15s").unwrap(),
+            Duration::from_secs(24 * 3600 + 2 * 3600 + 30 * 60 + 15)
+        );
+        assert_eq!(
+            parse_duration("1W").unwrap(),
+            Duration::from_secs(7 * 24 * 3600)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for raw in ["", "m", "10x", "1h30", "-5m", "99999999999999999999w"] {
+            assert!(
+                matches!(parse_duration(raw), Err(ConfigFieldError::MalformedData)),
+                "{raw}"
+            );
+        }
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+        assert_eq!(format_duration(Duration::from_secs(90)), "1m30s");
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86400 + 3600)),
+            "2d1h"
+        );
+        let duration = Duration::from_secs(86400 + 2 * 3600 + 3 * 60 + 4);
+        assert_eq!(
+            parse_duration(&format_duration(duration)).unwrap(),
+            duration
+        );
+    }
+
+    #[test]
+    fn parses_raw_values() {
+        assert!(matches!(
+            ConfigEntryType::Bool.parse_raw(" Yes "),
+            Ok(ConfigValue::Bool(true))
+        ));
+        assert!(matches!(
+            ConfigEntryType::I64.parse_raw("-3"),
+            Ok(ConfigValue::I64(-3))
+        ));
+        assert!(matches!(
+            ConfigEntryType::Duration.parse_raw("2m"),
+            Ok(ConfigValue::U64(120))
+        ));
+        assert!(matches!(
+            ConfigEntryType::Role.parse_raw("<@&123>"),
+            Ok(ConfigValue::U64(123))
+        ));
+        assert!(matches!(
+            ConfigEntryType::Channel(vec![]).parse_raw("<#456>"),
+            Ok(ConfigValue::U64(456))
+        ));
+        assert_eq!(
+            ConfigEntryType::RoleList
+                .parse_raw("<@&1>, <@&2> 3")
+                .unwrap()
+                .to_u64_vec()
+                .unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            ConfigEntryType::StringList
+                .parse_raw("a, b,,c")
+                .unwrap()
+                .to_string_vec()
+                .unwrap(),
+            vec!["a", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_raw_values() {
+        let choices = ConfigEntryType::Enum(vec!["low".to_string(), "high".to_string()]);
+        assert!(choices.parse_raw("high").is_ok());
+        for (field_type, raw) in [
+            (&choices, "medium"),
+            (&ConfigEntryType::Bool, "maybe"),
+            (&ConfigEntryType::U64, "-1"),
+            (&ConfigEntryType::User, "<@abc>"),
+            (&ConfigEntryType::ChannelList(vec![]), "<#1> two"),
+        ] {
+            assert!(
+                matches!(
+                    field_type.parse_raw(raw),
+                    Err(ConfigFieldError::MalformedData)
+                ),
+                "{raw}"
+            );
+        }
+    }
+
+    #[test]
+    fn displays_values() {
+        let display = |field_type: ConfigEntryType, raw: &str| {
+            field_type
+                .display(field_type.parse_raw(raw).unwrap())
+                .unwrap()
+        };
+        assert_eq!(display(ConfigEntryType::Duration, "3600"), "1h");
+        assert_eq!(display(ConfigEntryType::Role, "1"), "<@&1>");
+        assert_eq!(display(ConfigEntryType::User, "2"), "<@2>");
+        assert_eq!(display(ConfigEntryType::Channel(vec![]), "3"), "<#3>");
+        assert_eq!(display(ConfigEntryType::RoleList, ""), "none");
+        assert_eq!(
+            display(ConfigEntryType::ChannelList(vec![]), "1 2"),
+            "<#1>, <#2>"
+        );
+        assert_eq!(display(ConfigEntryType::StringList, "a,b"), "`a`, `b`");
+        assert!(matches!(
+            ConfigEntryType::Bool.display(ConfigValue::U64(1)),
+            Err(ConfigFieldError::ValueWrongType)
+        ));
+    }
+}
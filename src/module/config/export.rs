@@ -0,0 +1,238 @@
+use super::{ConfigError, ConfigManager, history::record_history};
+use crate::{
+    core::{
+        module::{DragonBotModule, get_module, get_module_by_id},
+        modules::ModuleConfigHolder,
+    },
+    module::{errors::ModuleError, module_manager::ModuleManager, permissions::PermissionsManager},
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serenity::all::{GuildId, UserId};
+use std::collections::{BTreeMap, HashSet};
+
+const EXPORT_VERSION: u64 = 1;
+const DIFF_VALUE_MAX_LEN: usize = 80;
+
+#[derive(Serialize, Deserialize)]
+pub struct ConfigExport {
+    pub version: u64,
+    pub guild: GuildId,
+    pub modules: BTreeMap<String, Value>,
+}
+
+pub struct PendingImport {
+    configs: Vec<ModuleConfigHolder>,
+    pub changes: Vec<ConfigChange>,
+    pub skipped_secrets: Vec<String>,
+}
+
+pub struct ConfigChange {
+    pub module: String,
+    pub field: Option<String>,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl ConfigChange {
+    pub fn path(&self) -> String {
+        match &self.field {
+            Some(field) => format!("{}.{field}", self.module),
+            None => self.module.clone(),
+        }
+    }
+}
+
+fn secret_fields(module_id: &str) -> Result<HashSet<&'static str>, ModuleError> {
+    Ok(get_module_by_id(module_id)?
+        .get_config_fields()
+        .into_iter()
+        .filter(|(_, field)| field.secret)
+        .map(|(name, _)| name)
+        .collect())
+}
+
+fn render_value(value: &Value) -> String {
+    let mut rendered = value.to_string();
+    if rendered.len() > DIFF_VALUE_MAX_LEN {
+        let mut end = DIFF_VALUE_MAX_LEN;
+        while !rendered.is_char_boundary(end) {
+            end -= 1;
+        }
+        rendered.truncate(end);
+        rendered.push('…');
+    }
+    rendered
+}
+
+impl ConfigManager {
+    pub async fn export_guild(&self, guild: GuildId) -> Result<ConfigExport, ModuleError> {
+        let module_manager = get_module::<ModuleManager>()?;
+        let mut module_ids = module_manager
+            .module::<ModuleManager>()
+            .get_all_active_module_ids(guild)
+            .await?;
+        for always in [ModuleManager::module_id(), PermissionsManager::module_id()] {
+            if !module_ids.iter().any(|id| id == always) {
+                module_ids.push(always.to_string());
+            }
+        }
+
+        let mut modules = BTreeMap::new();
+        for module_id in module_ids {
+            let module = get_module_by_id(&module_id)?;
+            let mut json = module.get_config(guild).await?.to_json()?;
+            if let Value::Object(fields) = &mut json {
+                for field in secret_fields(&module_id)?
+                    .into_iter()
+                    .chain(module.inherited_fields(guild).await?)
+                {
+                    fields.remove(field);
+                }
+            }
+            modules.insert(module_id, json);
+        }
+
+        Ok(ConfigExport {
+            version: EXPORT_VERSION,
+            guild,
+            modules,
+        })
+    }
+
+    /// validates an export against the guild's current config without writing anything.
+    /// secret fields are never imported, the guild keeps its current values for them.
+    pub async fn prepare_import(
+        &self,
+        guild: GuildId,
+        raw: &[u8],
+    ) -> Result<PendingImport, ModuleError> {
+        let export: ConfigExport = serde_json::from_slice(raw)
+            .map_err(|err| ConfigError::ImportInvalid(format!("not a config export: {err}")))?;
+        if export.version != EXPORT_VERSION {
+            Err(ConfigError::ImportInvalid(format!(
+                "unsupported export version {}",
+                export.version
+            )))?;
+        }
+
+        let mut pending = PendingImport {
+            configs: vec![],
+            changes: vec![],
+            skipped_secrets: vec![],
+        };
+        for (module_id, imported) in export.modules {
+            let module = get_module_by_id(&module_id)
+                .map_err(|_| ConfigError::ImportInvalid(format!("unknown module `{module_id}`")))?;
+            let secrets = secret_fields(&module_id)?;
+            let current = module.get_config(guild).await?.to_json()?;
+
+            let merged = match (&current, imported) {
+                (Value::Object(current_fields), Value::Object(imported_fields)) => {
+                    let mut merged_fields = current_fields.clone();
+                    for (field, value) in imported_fields {
+                        if secrets.contains(field.as_str()) {
+                            pending.skipped_secrets.push(format!("{module_id}.{field}"));
+                            continue;
+                        }
+                        merged_fields.insert(field, value);
+                    }
+                    Value::Object(merged_fields)
+                }
+                (_, imported) => imported,
+            };
+
+            match (&current, &merged) {
+                (Value::Object(current_fields), Value::Object(merged_fields)) => {
+                    let mut fields: Vec<&String> =
+                        current_fields.keys().chain(merged_fields.keys()).collect();
+                    fields.sort();
+                    fields.dedup();
+                    for field in fields {
+                        if secrets.contains(field.as_str()) {
+                            continue;
+                        }
+                        let old = current_fields.get(field);
+                        let new = merged_fields.get(field);
+                        if old != new {
+                            pending.changes.push(ConfigChange {
+                                module: module_id.clone(),
+                                field: Some(field.clone()),
+                                old: old.map(render_value),
+                                new: new.map(render_value),
+                            });
+                        }
+                    }
+                }
+                (current, merged) if current != merged => pending.changes.push(ConfigChange {
+                    module: module_id.clone(),
+                    field: None,
+                    old: Some(render_value(current)),
+                    new: Some(render_value(merged)),
+                }),
+                _ => {}
+            }
+
+            pending
+                .configs
+                .push(module.config_from_json(merged).map_err(|err| match err {
+                    ModuleError::ConfigError(ConfigError::SerdeError(err)) => {
+                        ConfigError::ImportInvalid(format!("`{module_id}`: {err}"))
+                    }
+                    _ => ConfigError::ImportInvalid(format!("`{module_id}`: {err}")),
+                })?);
+        }
+
+        Ok(pending)
+    }
+
+    /// writes every config of the import, or none of them if any write fails.
+    pub async fn apply_import(
+        &self,
+        guild: GuildId,
+        pending: PendingImport,
+        user: UserId,
+    ) -> Result<(), ModuleError> {
+        let mut changes = Vec::with_capacity(pending.configs.len());
+        for config in &pending.configs {
+            changes.push((config.module_id(), config.changes_from_disk(guild).await?));
+        }
+
+        let mut staged = Vec::with_capacity(pending.configs.len());
+        for config in &pending.configs {
+            match config.stage(guild).await {
+                Ok(config) => staged.push(config),
+                Err(err) => {
+                    for config in staged {
+                        config.discard().await;
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        let mut committed = Vec::with_capacity(staged.len());
+        let mut staged = staged.into_iter();
+        while let Some(config) = staged.next() {
+            match config.commit_reversible().await {
+                Ok(config) => committed.push(config),
+                Err(err) => {
+                    for config in staged {
+                        config.discard().await;
+                    }
+                    for config in committed.into_iter().rev() {
+                        config.revert().await;
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        for (module, changes) in changes {
+            if let Err(err) = record_history(Some(guild), module, changes, user).await {
+                warn!("failed to record {module} config history for {guild}: {err:?}");
+            }
+        }
+        Ok(())
+    }
+}
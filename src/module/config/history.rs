@@ -0,0 +1,157 @@
+use super::{ConfigError, ModuleConfig, entry::ConfigValue};
+use crate::{module::errors::ModuleError, util::data_path};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, GuildId, UserId};
+use tokio::{
+    fs::{create_dir_all, read_to_string, write},
+    sync::Mutex,
+};
+
+const HISTORY_MAX_ENTRIES: usize = 500;
+
+static HISTORY_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// a changed field, values are `None` for secret fields.
+pub struct FieldChange {
+    pub field: String,
+    pub channel: Option<ChannelId>,
+    pub old: Option<ConfigValue>,
+    pub new: Option<ConfigValue>,
+    pub secret: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConfigHistoryEntry {
+    pub id: u64,
+    /// `None` for changes to the global config.
+    pub guild: Option<GuildId>,
+    pub module: String,
+    pub field: String,
+    #[serde(default)]
+    pub channel: Option<ChannelId>,
+    pub old: Option<ConfigValue>,
+    pub new: Option<ConfigValue>,
+    pub secret: bool,
+    pub user: UserId,
+    pub timestamp: i64,
+}
+
+pub fn diff_config_fields<C: ModuleConfig>(old: &C, new: &C) -> Vec<FieldChange> {
+    let mut fields: Vec<_> = C::get_config_fields().into_iter().collect();
+    fields.sort_by_key(|(name, _)| *name);
+
+    let mut changes = vec![];
+    for (name, field) in fields {
+        let old = old.get_config_entry(name).ok();
+        let new = new.get_config_entry(name).ok();
+        if old == new {
+            continue;
+        }
+        changes.push(if field.secret {
+            FieldChange {
+                field: name.to_string(),
+                channel: None,
+                old: None,
+                new: None,
+                secret: true,
+            }
+        } else {
+            FieldChange {
+                field: name.to_string(),
+                channel: None,
+                old,
+                new,
+                secret: false,
+            }
+        });
+    }
+    changes
+}
+
+/// `global.json` holds the changes to the global config.
+async fn history_file(guild: Option<GuildId>) -> Result<std::path::PathBuf, ModuleError> {
+    let path = data_path().await?.join("config-history");
+    if !path.exists() {
+        create_dir_all(&path).await.map_err(ConfigError::IoError)?;
+    }
+    let name = match guild {
+        Some(guild) => guild.to_string(),
+        None => "global".to_string(),
+    };
+    Ok(path.join(format!("{name}.json")))
+}
+
+async fn read_history(guild: Option<GuildId>) -> Result<Vec<ConfigHistoryEntry>, ModuleError> {
+    let path = history_file(guild).await?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let json = read_to_string(path).await.map_err(ConfigError::IoError)?;
+    Ok(serde_json::from_str(&json).map_err(ConfigError::SerdeError)?)
+}
+
+pub async fn record_history(
+    guild: Option<GuildId>,
+    module: &str,
+    changes: Vec<FieldChange>,
+    user: UserId,
+) -> Result<(), ModuleError> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let _lock = HISTORY_LOCK.lock().await;
+    let mut history = read_history(guild).await?;
+    let first_id = history.last().map(|entry| entry.id + 1).unwrap_or(1);
+    let timestamp = Utc::now().timestamp();
+    for (id, change) in (first_id..).zip(changes) {
+        history.push(ConfigHistoryEntry {
+            id,
+            guild,
+            module: module.to_string(),
+            field: change.field,
+            channel: change.channel,
+            old: change.old,
+            new: change.new,
+            secret: change.secret,
+            user,
+            timestamp,
+        });
+    }
+    if history.len() > HISTORY_MAX_ENTRIES {
+        history.drain(..history.len() - HISTORY_MAX_ENTRIES);
+    }
+
+    let json = serde_json::to_string(&history).map_err(ConfigError::SerdeError)?;
+    write(history_file(guild).await?, json)
+        .await
+        .map_err(ConfigError::IoError)?;
+    Ok(())
+}
+
+/// newest entries first.
+pub async fn module_history(
+    guild: GuildId,
+    module: &str,
+) -> Result<Vec<ConfigHistoryEntry>, ModuleError> {
+    let _lock = HISTORY_LOCK.lock().await;
+    let mut history: Vec<_> = read_history(Some(guild))
+        .await?
+        .into_iter()
+        .filter(|entry| entry.module == module)
+        .collect();
+    history.reverse();
+    Ok(history)
+}
+
+pub async fn history_entry(
+    guild: GuildId,
+    id: u64,
+) -> Result<Option<ConfigHistoryEntry>, ModuleError> {
+    let _lock = HISTORY_LOCK.lock().await;
+    Ok(read_history(Some(guild))
+        .await?
+        .into_iter()
+        .find(|entry| entry.id == id))
+}
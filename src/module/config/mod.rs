@@ -1,22 +1,69 @@
-use super::errors::ModuleError;
-use crate::{core::module::DragonBotModule, util::config_path};
+use super::errors::{ModuleError, UserFacingError};
+use crate::{
+    core::{module::DragonBotModule, permissions::ModulePermission},
+    util::{config_path, global_config_path},
+};
+use channel::{channel_chain, read_channel_overrides};
 use entry::{ConfigField, ConfigFieldError, ConfigValue};
+use log::warn;
 use serde::{Deserialize, Serialize};
-use serenity::{all::GuildId, async_trait};
-use std::{collections::HashMap, io};
+use serde_json::Value;
+use serenity::{
+    all::{ChannelId, Context, GuildId},
+    async_trait,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    io,
+    path::{Path, PathBuf},
+};
 use tokio::fs::read_to_string;
+use watcher::{last_good_config, remember_config};
 
+pub mod channel;
 mod command;
 pub mod entry;
+mod export;
+pub mod history;
 mod permission;
+pub mod secret;
+pub mod watcher;
 
 #[derive(Debug)]
 pub enum ConfigError {
     SerdeError(serde_json::Error),
     IoError(io::Error),
+    SecretKeyMissing,
+    SecretCrypto,
+    ImportInvalid(String),
 }
 
-pub trait ModuleConfig: Serialize + for<'de> Deserialize<'de> + Default + Send {
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::SerdeError(err) => write!(f, "invalid config json: {err}"),
+            ConfigError::IoError(err) => write!(f, "config io error: {err}"),
+            ConfigError::SecretKeyMissing => write!(
+                f,
+                "Secret fields cannot be set, the bot has no secret key configured."
+            ),
+            ConfigError::SecretCrypto => write!(f, "failed to encrypt or decrypt a secret"),
+            ConfigError::ImportInvalid(reason) => write!(f, "The import is invalid: {reason}"),
+        }
+    }
+}
+
+impl UserFacingError for ConfigError {
+    fn is_user_error(&self) -> bool {
+        matches!(
+            self,
+            ConfigError::ImportInvalid(_) | ConfigError::SecretKeyMissing
+        )
+    }
+}
+
+pub trait ModuleConfig: Serialize + for<'de> Deserialize<'de> + Default + Send + Sync {
     fn get_config_fields() -> HashMap<&'static str, ConfigField>;
     fn get_config_entry(&self, field: &str) -> Result<ConfigValue, ConfigFieldError>;
     fn set_config_entry(&mut self, field: &str, value: ConfigValue)
@@ -50,34 +97,270 @@ pub trait DragonModuleConfigurable {
         Self::Config::get_config_fields()
     }
 
+    /// required to read any field of the module, on top of `VIEW_CONFIG`.
+    fn config_view_permission() -> Option<ModulePermission> {
+        None
+    }
+
+    /// required to change any field of the module, on top of `EDIT_CONFIG`.
+    fn config_edit_permission() -> Option<ModulePermission> {
+        None
+    }
+
+    async fn config_file(guild: GuildId) -> Result<PathBuf, ModuleError> {
+        Ok(config_path(&guild)
+            .await?
+            .join(format!("{}.json", Self::Module::module_id())))
+    }
+
     async fn get_full_config(guild: GuildId) -> Result<Self::Config, ModuleError> {
-        let config_path = config_path(&guild)
+        read_config_file(&Self::config_file(guild).await?).await
+    }
+
+    async fn global_config_file() -> Result<PathBuf, ModuleError> {
+        Ok(global_config_path()
             .await?
-            .join(format!("{}.json", Self::Module::module_id()));
-        if !config_path.exists() {
-            return Ok(Self::Config::default());
+            .join(format!("{}.json", Self::Module::module_id())))
+    }
+
+    /// the bot-wide config, only bot owners can change it.
+    async fn get_global_config() -> Result<Self::Config, ModuleError> {
+        read_config_file(&Self::global_config_file().await?).await
+    }
+
+    /// the inheritable fields missing from the guild's config file.
+    async fn inherited_fields(guild: GuildId) -> Result<HashSet<&'static str>, ModuleError> {
+        let keys = read_config_keys(&Self::config_file(guild).await?).await?;
+        Ok(Self::get_config_fields()
+            .into_iter()
+            .filter(|(name, field)| field.inheritable && !keys.contains(*name))
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// the guild's config layered on the global config, inheritable fields the guild does
+    /// not set take the global value.
+    async fn get_guild_config(guild: GuildId) -> Result<Self::Config, ModuleError> {
+        let mut config = Self::get_full_config(guild).await?;
+        let inherited = Self::inherited_fields(guild).await?;
+        if inherited.is_empty() || !Self::global_config_file().await?.exists() {
+            return Ok(config);
         }
 
-        let json = read_to_string(config_path)
-            .await
-            .map_err(ConfigError::IoError)?;
-        Ok(serde_json::from_str(&json).map_err(ConfigError::SerdeError)?)
+        let global = Self::get_global_config().await?;
+        for name in inherited {
+            config.set_config_entry(name, global.get_config_entry(name)?)?;
+        }
+        Ok(config)
+    }
+
+    /// the config as seen from a channel. channel overridable fields resolve in the order
+    /// channel, parent category, guild, default.
+    async fn get_effective_config(
+        ctx: &Context,
+        guild: GuildId,
+        channel: ChannelId,
+    ) -> Result<Self::Config, ModuleError> {
+        let mut config = Self::get_guild_config(guild).await?;
+        let overrides = read_channel_overrides(guild, Self::Module::module_id()).await?;
+        if overrides.channels.is_empty() {
+            return Ok(config);
+        }
+
+        let chain = channel_chain(ctx, channel).await;
+        for (name, field) in Self::get_config_fields() {
+            if !field.channel_overridable {
+                continue;
+            }
+            if let Some((_, value)) = overrides.resolve(&chain, name) {
+                config.set_config_entry(name, value.clone())?;
+            }
+        }
+        Ok(config)
+    }
+
+    /// inherited fields stay inherited unless they changed or are in `set`, fields in
+    /// `inherit` take the global value again.
+    async fn stage_guild_config(
+        guild: GuildId,
+        config: &Self::Config,
+        set: &[&str],
+        inherit: &[&str],
+    ) -> Result<StagedConfig, ModuleError> {
+        let current = Self::get_full_config(guild).await?;
+        let mut inherited = Self::inherited_fields(guild).await?;
+        inherited.retain(|name| {
+            !set.contains(name)
+                && current.get_config_entry(name).ok() == config.get_config_entry(name).ok()
+        });
+        for (name, field) in Self::get_config_fields() {
+            if field.inheritable && inherit.contains(&name) {
+                inherited.insert(name);
+            }
+        }
+        stage_config_file(Self::config_file(guild).await?, config, &inherited).await
+    }
+
+    async fn stage_full_config(
+        guild: GuildId,
+        config: &Self::Config,
+    ) -> Result<StagedConfig, ModuleError> {
+        Self::stage_guild_config(guild, config, &[], &[]).await
     }
 
     async fn set_full_config(guild: GuildId, config: Self::Config) -> Result<(), ModuleError> {
-        let config_path = config_path(&guild)
+        Self::stage_full_config(guild, &config)
+            .await?
+            .commit()
+            .await
+    }
+
+    async fn set_global_config(config: Self::Config) -> Result<(), ModuleError> {
+        stage_config_file(Self::global_config_file().await?, &config, &HashSet::new())
             .await?
-            .join(format!("{}.json", Self::Module::module_id()));
-        let json = serde_json::to_string(&config)
-            .map_err(ConfigError::SerdeError)?
-            .to_string();
+            .commit()
+            .await
+    }
+}
+
+async fn read_config_file<C: ModuleConfig>(config_path: &Path) -> Result<C, ModuleError> {
+    if !config_path.exists() {
+        return Ok(C::default());
+    }
+
+    let json = read_to_string(config_path)
+        .await
+        .map_err(ConfigError::IoError)?;
+    match serde_json::from_str(&json) {
+        Ok(config) => {
+            remember_config(config_path, &json);
+            Ok(config)
+        }
+        Err(err) => {
+            let Some(last_good) = last_good_config(config_path) else {
+                Err(ConfigError::SerdeError(err))?
+            };
+            warn!("{config_path:?} failed to parse, using the last valid config: {err}");
+            Ok(serde_json::from_str(&last_good).map_err(ConfigError::SerdeError)?)
+        }
+    }
+}
+
+/// the keys of a config file, from its last valid contents if it fails to parse.
+async fn read_config_keys(config_path: &Path) -> Result<HashSet<String>, ModuleError> {
+    if !config_path.exists() {
+        return Ok(HashSet::new());
+    }
+
+    let json = read_to_string(config_path)
+        .await
+        .map_err(ConfigError::IoError)?;
+    let value = match serde_json::from_str(&json) {
+        Ok(value) => value,
+        Err(err) => {
+            let Some(last_good) = last_good_config(config_path) else {
+                Err(ConfigError::SerdeError(err))?
+            };
+            serde_json::from_str(&last_good).map_err(ConfigError::SerdeError)?
+        }
+    };
+    Ok(match value {
+        Value::Object(fields) => fields.into_iter().map(|(key, _)| key).collect(),
+        _ => HashSet::new(),
+    })
+}
+
+/// `omit` are the fields left out of the file.
+async fn stage_config_file<C: ModuleConfig>(
+    target: PathBuf,
+    config: &C,
+    omit: &HashSet<&str>,
+) -> Result<StagedConfig, ModuleError> {
+    let staged = target.with_extension("json.staged");
+    let mut value = serde_json::to_value(config).map_err(ConfigError::SerdeError)?;
+    if let Value::Object(fields) = &mut value {
+        fields.retain(|key, _| !omit.contains(key.as_str()));
+    }
+    let json = serde_json::to_string(&value).map_err(ConfigError::SerdeError)?;
 
-        tokio::fs::write(config_path, json)
+    tokio::fs::write(&staged, &json)
+        .await
+        .map_err(ConfigError::IoError)?;
+
+    Ok(StagedConfig {
+        staged,
+        target,
+        json,
+    })
+}
+
+/// a config written next to its target file, moved into place by `commit`.
+pub struct StagedConfig {
+    staged: PathBuf,
+    target: PathBuf,
+    json: String,
+}
+
+impl StagedConfig {
+    pub async fn commit(self) -> Result<(), ModuleError> {
+        // remembered first so the watcher does not treat our own write as an external edit
+        remember_config(&self.target, &self.json);
+        tokio::fs::rename(&self.staged, &self.target)
             .await
             .map_err(ConfigError::IoError)?;
-
         Ok(())
     }
+
+    /// commits and keeps the previous contents of the target, the staged file is removed if
+    /// the commit fails.
+    pub async fn commit_reversible(self) -> Result<CommittedConfig, ModuleError> {
+        let previous = match self.target.exists() {
+            true => match read_to_string(&self.target).await {
+                Ok(previous) => Some(previous),
+                Err(err) => {
+                    self.discard().await;
+                    return Err(ConfigError::IoError(err).into());
+                }
+            },
+            false => None,
+        };
+        let target = self.target.clone();
+        let staged = self.staged.clone();
+        if let Err(err) = self.commit().await {
+            if let Err(err) = tokio::fs::remove_file(&staged).await {
+                warn!("failed to remove staged config {staged:?}: {err}");
+            }
+            return Err(err);
+        }
+        Ok(CommittedConfig { target, previous })
+    }
+
+    pub async fn discard(self) {
+        if let Err(err) = tokio::fs::remove_file(&self.staged).await {
+            warn!("failed to remove staged config {:?}: {err}", self.staged);
+        }
+    }
+}
+
+/// a committed config, `revert` puts back the contents the target had before.
+pub struct CommittedConfig {
+    target: PathBuf,
+    previous: Option<String>,
+}
+
+impl CommittedConfig {
+    pub async fn revert(self) {
+        let result = match &self.previous {
+            Some(previous) => {
+                remember_config(&self.target, previous);
+                tokio::fs::write(&self.target, previous).await
+            }
+            None => tokio::fs::remove_file(&self.target).await,
+        };
+        if let Err(err) = result {
+            warn!("failed to restore config {:?}: {err}", self.target);
+        }
+    }
 }
 
 #[derive(Default)]
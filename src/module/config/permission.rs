@@ -1,14 +1,23 @@
 use super::ConfigManager;
 use crate::core::permissions::{DragonModulePermission, ModulePermission};
+use serenity::all::Permissions;
 
 pub const EDIT_CONFIG: ModulePermission = ModulePermission::new(
     "config-manager",
     "edit-config",
     "update the configs for a guild's modules",
-);
+)
+.discord_default(Permissions::MANAGE_GUILD);
+
+pub const VIEW_CONFIG: ModulePermission = ModulePermission::new(
+    "config-manager",
+    "view-config",
+    "view the configs for a guild's modules",
+)
+.discord_default(Permissions::MANAGE_GUILD);
 
 impl DragonModulePermission for ConfigManager {
     async fn all_permissions(&self) -> Vec<ModulePermission> {
-        vec![EDIT_CONFIG]
+        vec![EDIT_CONFIG, VIEW_CONFIG]
     }
 }
@@ -0,0 +1,202 @@
+use super::ConfigError;
+use crate::module::errors::ModuleError;
+use aes_gcm::{
+    Aes256Gcm, Key, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng},
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use log::warn;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de, ser};
+use sha2::{Digest, Sha256};
+use std::{env, fmt, sync::OnceLock};
+
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+const MIN_KEY_LEN: usize = 32;
+
+static SECRET_KEY: OnceLock<Key<Aes256Gcm>> = OnceLock::new();
+
+/// loads the key used to encrypt secret config values from `CONFIG_SECRET_KEY`.
+/// the key is never stored next to the configs, without it secret fields cannot be set.
+///
+/// the variable is hashed into the key as is, not stretched, so it must already be a random
+/// key such as the output of `openssl rand -base64 32` rather than a memorable passphrase.
+pub async fn init_secret_key() -> Result<(), ModuleError> {
+    if SECRET_KEY.get().is_some() {
+        return Ok(());
+    }
+
+    let Ok(passphrase) = env::var("CONFIG_SECRET_KEY") else {
+        warn!("CONFIG_SECRET_KEY not set, secret config fields cannot be set.");
+        return Ok(());
+    };
+
+    if passphrase.len() < MIN_KEY_LEN {
+        warn!("CONFIG_SECRET_KEY is shorter than {MIN_KEY_LEN} characters, use a random key.");
+    }
+    _ = SECRET_KEY.set(Sha256::digest(passphrase.as_bytes()));
+    Ok(())
+}
+
+pub fn secret_key_available() -> bool {
+    SECRET_KEY.get().is_some()
+}
+
+fn cipher() -> Result<Aes256Gcm, ConfigError> {
+    SECRET_KEY
+        .get()
+        .map(Aes256Gcm::new)
+        .ok_or(ConfigError::SecretKeyMissing)
+}
+
+/// a config value that is never displayed and is encrypted when written to disk.
+#[derive(Default, Clone, PartialEq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_set(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    fn encrypt(&self) -> Result<String, ConfigError> {
+        let nonce = Aes256Gcm::generate_nonce(OsRng);
+        let mut data = nonce.to_vec();
+        data.append(
+            &mut cipher()?
+                .encrypt(&nonce, self.0.as_bytes())
+                .map_err(|_| ConfigError::SecretCrypto)?,
+        );
+        Ok(format!(
+            "{ENCRYPTED_PREFIX}{}",
+            BASE64_STANDARD.encode(data)
+        ))
+    }
+
+    fn decrypt(stored: &str) -> Result<Self, ConfigError> {
+        let Some(encoded) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+            // written before the field was marked secret, re-encrypted on the next save
+            return Ok(Self::new(stored));
+        };
+        let data = BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|_| ConfigError::SecretCrypto)?;
+        if data.len() < NONCE_LEN {
+            return Err(ConfigError::SecretCrypto);
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plain = cipher()?
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| ConfigError::SecretCrypto)?;
+        Ok(Self(
+            String::from_utf8(plain).map_err(|_| ConfigError::SecretCrypto)?,
+        ))
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !self.is_set() {
+            return serializer.serialize_str("");
+        }
+        if !secret_key_available() {
+            // only values stored before the field was marked secret can be loaded without a key,
+            // keep them as they are so the rest of the config can still be saved
+            warn!("CONFIG_SECRET_KEY not set, writing a secret config value unencrypted.");
+            return serializer.serialize_str(&self.0);
+        }
+        serializer.serialize_str(
+            &self
+                .encrypt()
+                .map_err(|err| ser::Error::custom(format!("{err:?}")))?,
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let stored = String::deserialize(deserializer)?;
+        Secret::decrypt(&stored).map_err(|err| de::Error::custom(format!("{err:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_test_key() {
+        SECRET_KEY.get_or_init(|| Sha256::digest(b"test passphrase"));
+    }
+
+    fn stored(secret: &Secret) -> String {
+        serde_json::from_str(&serde_json::to_string(secret).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        init_test_key();
+        let secret = Secret::new("hunter2");
+        let json = serde_json::to_string(&secret).unwrap();
+        assert!(!json.contains("hunter2"));
+        assert_eq!(serde_json::from_str::<Secret>(&json).unwrap(), secret);
+    }
+
+    #[test]
+    fn encrypts_with_a_fresh_nonce() {
+        init_test_key();
+        let secret = Secret::new("hunter2");
+        let first = stored(&secret);
+        assert!(first.starts_with(ENCRYPTED_PREFIX));
+        assert_ne!(first, stored(&secret));
+    }
+
+    #[test]
+    fn unset_secrets_are_not_encrypted() {
+        init_test_key();
+        assert_eq!(stored(&Secret::default()), "");
+        assert!(!Secret::decrypt("").unwrap().is_set());
+    }
+
+    #[test]
+    fn reads_values_stored_before_encryption() {
+        init_test_key();
+        assert_eq!(Secret::decrypt("plain").unwrap().expose(), "plain");
+    }
+
+    #[test]
+    fn rejects_tampered_values() {
+        init_test_key();
+        let mut data = BASE64_STANDARD
+            .decode(&stored(&Secret::new("hunter2"))[ENCRYPTED_PREFIX.len()..])
+            .unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        let tampered = format!("{ENCRYPTED_PREFIX}{}", BASE64_STANDARD.encode(data));
+        assert!(matches!(
+            Secret::decrypt(&tampered),
+            Err(ConfigError::SecretCrypto)
+        ));
+        assert!(matches!(
+            Secret::decrypt(&format!("{ENCRYPTED_PREFIX}AAAA")),
+            Err(ConfigError::SecretCrypto)
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+use super::ConfigError;
+use crate::{
+    core::module::{get_module, get_module_by_id, get_module_by_id_mut},
+    module::{
+        errors::{ErrorManager, ErrorOrigin},
+        module_manager::ModuleManager,
+    },
+    util::data_path,
+};
+use log::{debug, error, info, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serenity::all::{Context, GuildId};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{fs::read_to_string, sync::mpsc, time::sleep};
+
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+static WATCHING: AtomicBool = AtomicBool::new(false);
+
+/// the last config file contents that parsed, by path.
+static LAST_GOOD: LazyLock<Mutex<HashMap<PathBuf, String>>> = LazyLock::new(Default::default);
+
+pub fn remember_config(path: &Path, json: &str) {
+    LAST_GOOD
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), json.to_string());
+}
+
+pub fn last_good_config(path: &Path) -> Option<String> {
+    LAST_GOOD.lock().unwrap().get(path).cloned()
+}
+
+fn forget_config(path: &Path) {
+    LAST_GOOD.lock().unwrap().remove(path);
+}
+
+/// watches `DATA_PATH/config` and reloads module configs that are edited on disk.
+pub async fn watch_config_dir(ctx: &Context) {
+    if WATCHING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let config_dir = match data_path().await {
+        Ok(path) => path.join("config"),
+        Err(err) => {
+            warn!("failed to get config directory, not watching for changes: {err:?}");
+            return;
+        }
+    };
+    if let Err(err) = tokio::fs::create_dir_all(&config_dir).await {
+        warn!("failed to create config directory, not watching for changes: {err}");
+        return;
+    }
+    let config_dir = config_dir.canonicalize().unwrap_or(config_dir);
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) => {
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) {
+                    for path in event.paths {
+                        _ = tx.send(path);
+                    }
+                }
+            }
+            Err(err) => warn!("config watcher error: {err}"),
+        });
+    let mut watcher: RecommendedWatcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            warn!("failed to create config watcher: {err}");
+            return;
+        }
+    };
+    if let Err(err) = watcher.watch(&config_dir, RecursiveMode::Recursive) {
+        warn!("failed to watch config directory: {err}");
+        return;
+    }
+    info!("Watching config directory: {config_dir:?}");
+
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        // the watcher stops when dropped
+        let _watcher = watcher;
+        while let Some(path) = rx.recv().await {
+            let mut changed = HashSet::from([path]);
+            sleep(RELOAD_DEBOUNCE).await;
+            while let Ok(path) = rx.try_recv() {
+                changed.insert(path);
+            }
+            for path in changed {
+                reload_config_file(&ctx, &config_dir, &path).await;
+            }
+        }
+    });
+}
+
+/// only `<guild>/<module>.json` or `global/<module>.json` directly in the config directory
+/// belongs to a module, `None` as guild for the global config.
+fn parse_config_path(config_dir: &Path, path: &Path) -> Option<(Option<GuildId>, String)> {
+    if path.extension()? != "json" || path.parent()?.parent()? != config_dir {
+        return None;
+    }
+    let module = path.file_stem()?.to_str()?.to_string();
+    let scope = path.parent()?.file_name()?.to_str()?;
+    if scope == "global" {
+        return Some((None, module));
+    }
+    Some((Some(GuildId::new(scope.parse().ok()?)), module))
+}
+
+async fn reload_config_file(ctx: &Context, config_dir: &Path, path: &Path) {
+    let Some((scope, module_id)) = parse_config_path(config_dir, path) else {
+        return;
+    };
+    let scope_name = match scope {
+        Some(guild) => guild.to_string(),
+        None => "every guild".to_string(),
+    };
+    let Ok(module) = get_module_by_id(&module_id) else {
+        debug!("ignoring config file of unknown module: {path:?}");
+        return;
+    };
+
+    if !path.exists() {
+        forget_config(path);
+        info!("{module_id} config for {scope_name} removed, using defaults");
+    } else {
+        let json = match read_to_string(path).await {
+            Ok(json) => json,
+            Err(err) => {
+                error!("failed to read {module_id} config for {scope_name}: {err}");
+                return;
+            }
+        };
+        if last_good_config(path).is_some_and(|last| last == json) {
+            return;
+        }
+
+        let parsed = serde_json::from_str(&json)
+            .map_err(|err| ConfigError::SerdeError(err).into())
+            .and_then(|value| module.config_from_json(value));
+        if let Err(err) = parsed {
+            error!("failed to reload {module_id} config for {scope_name}, keeping previous config");
+            let origin = ErrorOrigin::new(&module_id);
+            let origin = match scope {
+                Some(guild) => origin.guild(guild),
+                None => origin,
+            };
+            ErrorManager::report(ctx, origin, &err).await;
+            return;
+        }
+        remember_config(path, &json);
+        info!("reloaded {module_id} config for {scope_name}");
+    }
+
+    let guilds = match scope {
+        Some(guild) => vec![guild],
+        None => match get_module::<ModuleManager>() {
+            Ok(module_manager) => {
+                module_manager
+                    .module::<ModuleManager>()
+                    .active_guilds(ctx, &module_id)
+                    .await
+            }
+            Err(err) => {
+                warn!("failed to get module manager for config reload: {err:?}");
+                return;
+            }
+        },
+    };
+    let module = match get_module_by_id_mut(&module_id) {
+        Ok(module) => module,
+        Err(err) => {
+            warn!("failed to get {module_id} for config reload: {err:?}");
+            return;
+        }
+    };
+    for guild in guilds {
+        if let Err(err) = module.on_config_reloaded(ctx, guild).await {
+            warn!("{module_id} failed to handle config reload for {guild}");
+            ErrorManager::report(ctx, ErrorOrigin::new(&module_id).guild(guild), &err).await;
+        }
+    }
+}
@@ -1,21 +1,234 @@
+use super::{
+    ErrorManager, ErrorRecord, ModuleError,
+    permissions::{CLEAR_ERRORS, VIEW_ERRORS},
+};
+use crate::{
+    core::{
+        commands::DragonModuleCommand,
+        module::DragonBotModule,
+        modules::DragonBotModuleInstance,
+        permissions::{ModulePermission, assert_bot_owner, is_bot_owner},
+    },
+    module::bot_admin::{BotAdmin, BotAdminError},
+};
 use log::warn;
-use serenity::all::{CommandInteraction, Context, CreateCommand, GuildId};
+use serenity::all::{
+    CacheHttp, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponseFollowup, GuildId,
+};
 
-use crate::core::commands::DragonModuleCommand;
-
-use super::{ErrorManager, ModuleError};
+const PAGE_SIZE: usize = 10;
+const SUMMARY_MAX_LEN: usize = 200;
+const DETAIL_MAX_LEN: usize = 4000;
 
 impl DragonModuleCommand for ErrorManager {
     async fn command_builder(&self, _guild: GuildId) -> Option<CreateCommand> {
-        warn!("todo: ErrorManager::command_builder");
-        None
+        let mut module_option = CreateCommandOption::new(
+            CommandOptionType::String,
+            "module",
+            "only errors of this module",
+        );
+        for module in DragonBotModuleInstance::all_module_ids() {
+            module_option = module_option.add_string_choice(module, module);
+        }
+
+        Some(
+            CreateCommand::new(self.id())
+                .description("browse module errors")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "recent",
+                        "list the most recent errors",
+                    )
+                    .add_sub_option(module_option)
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::Integer, "page", "the page")
+                            .min_int_value(1),
+                    ),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "show",
+                        "show the full detail of an error",
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::Integer, "id", "the error id")
+                            .min_int_value(1)
+                            .required(true),
+                    ),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "clear",
+                        "clear the recorded errors of this guild",
+                    )
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::Boolean,
+                        "global",
+                        "clear the errors of every guild, bot owners in the staff guild only",
+                    )),
+                ),
+        )
+    }
+
+    fn subcommand_permissions(&self) -> Vec<(&'static str, Option<ModulePermission>)> {
+        vec![
+            ("recent", Some(VIEW_ERRORS)),
+            ("show", Some(VIEW_ERRORS)),
+            ("clear", Some(CLEAR_ERRORS)),
+        ]
     }
 
     async fn command_handle(
         &mut self,
-        _ctx: &Context,
-        _command: &CommandInteraction,
+        ctx: &Context,
+        command: &CommandInteraction,
     ) -> Result<(), ModuleError> {
-        todo!()
+        let Some(subcommand) = command.data.options.first() else {
+            self.command_help(ctx, command).await;
+            return Ok(());
+        };
+        let CommandDataOptionValue::SubCommand(data) = &subcommand.value else {
+            return Ok(());
+        };
+        let option = |name: &str| {
+            data.iter()
+                .find(|option| option.name == name)
+                .map(|option| &option.value)
+        };
+
+        // bot owners see the errors of every guild
+        let guild = match is_bot_owner(ctx, command.user.id).await? {
+            true => None,
+            false => command.guild_id,
+        };
+
+        match subcommand.name.as_str() {
+            "recent" => {
+                let module = option("module").and_then(|value| value.as_str());
+                let page = option("page")
+                    .and_then(|value| value.as_i64())
+                    .unwrap_or(1)
+                    .max(1) as usize;
+                let embed = recent_embed(&self.recent_errors(guild, module).await, page);
+                send_embed(ctx, command, embed).await;
+            }
+            "show" => {
+                let id = option("id")
+                    .and_then(|value| value.as_i64())
+                    .unwrap_or_default() as u64;
+                let embed = match self.error(guild, id).await? {
+                    Some(record) => detail_embed(&record),
+                    None => CreateEmbed::new().description(format!("No error with id `{id}`.")),
+                };
+                send_embed(ctx, command, embed).await;
+            }
+            "clear" => {
+                let global = option("global")
+                    .and_then(|value| value.as_bool())
+                    .unwrap_or_default();
+                let guild = match global {
+                    true => {
+                        if command.guild_id != BotAdmin::staff_guild().await? {
+                            Err(BotAdminError::NotStaffGuild)?;
+                        }
+                        if !assert_bot_owner(ctx, command).await? {
+                            return Ok(());
+                        }
+                        None
+                    }
+                    // `None` would clear every guild
+                    false => match command.guild_id {
+                        Some(guild) => Some(guild),
+                        None => return Ok(()),
+                    },
+                };
+                let cleared = self.clear_errors(guild).await?;
+                send_embed(
+                    ctx,
+                    command,
+                    CreateEmbed::new().description(format!("Cleared {cleared} errors.")),
+                )
+                .await;
+            }
+            e => warn!("unknown error manager subcommand: {e}"),
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn truncate(text: &str, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text.to_string();
+    }
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…", &text[..end])
+}
+
+fn recent_embed(records: &[ErrorRecord], page: usize) -> CreateEmbed {
+    let pages = records.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(pages);
+    let mut embed = CreateEmbed::new()
+        .title("Recent errors")
+        .footer(CreateEmbedFooter::new(format!(
+            "page {page}/{pages}, {} errors",
+            records.len()
+        )));
+    if records.is_empty() {
+        return embed.description("No errors recorded.");
+    }
+    for record in records.iter().skip((page - 1) * PAGE_SIZE).take(PAGE_SIZE) {
+        embed = embed.field(
+            format!("#{} {}", record.id, record.module),
+            format!(
+                "<t:{}:R> `{}` {}",
+                record.timestamp,
+                record.kind,
+                truncate(&record.detail, SUMMARY_MAX_LEN)
+            ),
+            false,
+        );
+    }
+    embed
+}
+
+pub(super) fn detail_embed(record: &ErrorRecord) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .title(format!("Error #{}", record.id))
+        .description(format!(
+            "```\n{}\n```",
+            truncate(&record.detail, DETAIL_MAX_LEN)
+        ))
+        .field("Module", &record.module, true)
+        .field("Kind", &record.kind, true)
+        .field("Time", format!("<t:{}:f>", record.timestamp), true);
+    if let Some(guild) = record.guild {
+        embed = embed.field("Guild", format!("`{guild}`"), true);
+    }
+    if let Some(user) = record.user {
+        embed = embed.field("User", format!("<@{user}>"), true);
+    }
+    if let Some(command) = &record.command {
+        embed = embed.field("Command", format!("`/{command}`"), true);
+    }
+    embed
+}
+
+async fn send_embed(ctx: &Context, command: &CommandInteraction, embed: CreateEmbed) {
+    if let Err(error) = command
+        .create_followup(
+            ctx.http(),
+            CreateInteractionResponseFollowup::new().embed(embed),
+        )
+        .await
+    {
+        warn!("failed to create followup: {error:?}");
     }
 }
@@ -0,0 +1,77 @@
+use super::ErrorManager;
+use crate::module::config::{
+    DragonModuleConfigurable, ModuleConfig,
+    entry::{ConfigEntryType, ConfigField, ConfigFieldError, ConfigValue},
+};
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, ChannelType};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ErrorManagerConfig {
+    #[serde(default)]
+    pub report_channel: Option<ChannelId>,
+    #[serde(default)]
+    pub staff_channel: Option<ChannelId>,
+}
+
+fn channel_value(channel: Option<ChannelId>) -> ConfigValue {
+    ConfigValue::U64(channel.map(|channel| channel.get()).unwrap_or(0))
+}
+
+fn value_channel(value: ConfigValue) -> Result<Option<ChannelId>, ConfigFieldError> {
+    Ok(Some(value.to_u64()?)
+        .filter(|channel| *channel != 0)
+        .map(ChannelId::new))
+}
+
+impl ModuleConfig for ErrorManagerConfig {
+    fn get_config_fields() -> HashMap<&'static str, ConfigField> {
+        [
+            (
+                "report_channel",
+                ConfigField::new(
+                    ConfigEntryType::Channel(vec![ChannelType::Text]),
+                    "channel the module errors of the guild are posted to",
+                )
+                .channel_overridable(),
+            ),
+            (
+                "staff_channel",
+                ConfigField::new(
+                    ConfigEntryType::Channel(vec![ChannelType::Text]),
+                    "channel the module errors of every guild are posted to",
+                )
+                .global_only(),
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn set_config_entry(
+        &mut self,
+        field: &str,
+        value: ConfigValue,
+    ) -> Result<(), ConfigFieldError> {
+        match field {
+            "report_channel" => self.report_channel = value_channel(value)?,
+            "staff_channel" => self.staff_channel = value_channel(value)?,
+            _ => return Err(ConfigFieldError::FieldNotFound),
+        }
+        Ok(())
+    }
+
+    fn get_config_entry(&self, field: &str) -> Result<ConfigValue, ConfigFieldError> {
+        match field {
+            "report_channel" => Ok(channel_value(self.report_channel)),
+            "staff_channel" => Ok(channel_value(self.staff_channel)),
+            _ => Err(ConfigFieldError::FieldNotFound),
+        }
+    }
+}
+
+impl DragonModuleConfigurable for ErrorManager {
+    type Config = ErrorManagerConfig;
+    type Module = ErrorManager;
+}
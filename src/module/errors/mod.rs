@@ -1,17 +1,33 @@
 use super::{
     super::core::module::GetModuleError,
+    bot_admin::BotAdminError,
     commands::CommandError,
-    config::{ConfigError, DragonModuleConfigurable, NoConfig},
+    config::{ConfigError, entry::ConfigFieldError},
     module_manager::ModuleManagerError,
     permissions::PermissionsError,
     tgdb::TgDbError,
 };
 use crate::core::module::DragonBotModule;
+use chrono::Utc;
 use log::error;
-use std::collections::HashMap;
+use panic::PanicError;
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, Context, GuildId, UserId};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display, Formatter},
+};
+use tokio::sync::Mutex;
 
 mod command;
+pub mod config;
+pub mod panic;
 mod permissions;
+mod report;
+mod store;
+
+/// errors kept in memory, older ones are only on disk.
+const ERROR_RING_SIZE: usize = 200;
 
 macro_rules! module_error_types {
     ( $( $type: ident ),+ ) => {
@@ -30,11 +46,39 @@ macro_rules! module_error_types {
             }
         )+
 
-        impl ErrorManager {
-            fn get_module_error_string(module: &impl DragonBotModule, error: &ModuleError) -> String where {
-                match error {
+        impl ModuleError {
+            /// the name of the variant, such as `TgDbError`.
+            pub fn kind(&self) -> &'static str {
+                match self {
+                    $(
+                        ModuleError::$type(_) => stringify!($type),
+                    )+
+                }
+            }
+
+            fn detail(&self) -> String {
+                match self {
+                    $(
+                        ModuleError::$type(err) => format!("{err:?}"),
+                    )+
+                }
+            }
+
+            /// the message shown to the user, `None` for unexpected errors.
+            pub fn user_message(&self) -> Option<String> {
+                match self {
+                    $(
+                        ModuleError::$type(err) => err.is_user_error().then(|| err.to_string()),
+                    )+
+                }
+            }
+        }
+
+        impl Display for ModuleError {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                match self {
                     $(
-                        ModuleError::$type(err) => format!("[{}][{}]: {:?}", module.id(), stringify!($type), err),
+                        ModuleError::$type(err) => err.fmt(f),
                     )+
                 }
             }
@@ -47,16 +91,102 @@ module_error_types! {
     ModuleManagerError,
     PermissionsError,
     ConfigError,
+    ConfigFieldError,
     CommandError,
-    GetModuleError
+    GetModuleError,
+    BotAdminError,
+    PanicError
 }
 
-#[derive(Default)]
-pub struct ErrorManager {
-    all_error_log: Vec<String>,
-    module_error_log: HashMap<String, Vec<String>>,
+/// an error that can be shown to users.
+pub trait UserFacingError: Display {
+    /// whether the error is caused by the user, such as an unknown module. the message of
+    /// other errors can contain internals like server addresses and is never shown to users.
+    fn is_user_error(&self) -> bool;
+}
+
+/// where an error happened, recorded by `ErrorManager::report`.
+pub struct ErrorOrigin {
+    pub module: String,
+    pub guild: Option<GuildId>,
+    pub channel: Option<ChannelId>,
+    pub user: Option<UserId>,
+    pub command: Option<String>,
+}
+
+impl ErrorOrigin {
+    pub fn new(module: &str) -> Self {
+        Self {
+            module: module.to_string(),
+            guild: None,
+            channel: None,
+            user: None,
+            command: None,
+        }
+    }
+
+    pub fn guild(mut self, guild: GuildId) -> Self {
+        self.guild = Some(guild);
+        self
+    }
+
+    /// the channel the error happened in, used to resolve channel overrides of the report
+    /// channel.
+    pub fn channel(mut self, channel: ChannelId) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    pub fn user(mut self, user: UserId) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    /// the full command path, such as `permissions-manager group create`.
+    pub fn command(mut self, command: String) -> Self {
+        self.command = Some(command);
+        self
+    }
+}
+
+/// a module error, `guild` is unset for errors outside of a guild.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ErrorRecord {
+    pub id: u64,
+    pub timestamp: i64,
+    pub guild: Option<GuildId>,
+    #[serde(default)]
+    pub channel: Option<ChannelId>,
+    pub module: String,
+    pub user: Option<UserId>,
+    pub command: Option<String>,
+    /// the `ModuleError` variant.
+    pub kind: String,
+    pub detail: String,
 }
 
+impl ErrorRecord {
+    pub fn summary(&self) -> String {
+        format!("[{}][{}]: {}", self.module, self.kind, self.detail)
+    }
+}
+
+/// the errors kept in memory, shared by every task that reports errors.
+struct ErrorState {
+    errors: VecDeque<ErrorRecord>,
+    next_id: u64,
+    loaded: bool,
+}
+
+static STATE: Mutex<ErrorState> = Mutex::const_new(ErrorState {
+    errors: VecDeque::new(),
+    next_id: 0,
+    loaded: false,
+});
+
+#[derive(Default)]
+pub struct ErrorManager;
+
 impl DragonBotModule for ErrorManager {
     fn module_id() -> &'static str
     where
@@ -64,22 +194,140 @@ impl DragonBotModule for ErrorManager {
     {
         "error-manager"
     }
+
+    async fn init(&mut self, _ctx: &Context) -> Result<(), ModuleError> {
+        STATE.lock().await.load().await
+    }
 }
 
-impl DragonModuleConfigurable for ErrorManager {
-    type Config = NoConfig;
-    type Module = ErrorManager;
+impl ErrorState {
+    /// fills the in-memory errors from disk once, keeping errors recorded while the store
+    /// could not be read.
+    async fn load(&mut self) -> Result<(), ModuleError> {
+        if self.loaded {
+            return Ok(());
+        }
+        let mut records = store::read_all_records().await?;
+        for record in self.errors.drain(..) {
+            if !records.iter().any(|stored| stored.id == record.id) {
+                records.push(record);
+            }
+        }
+        records.sort_by_key(|record| record.id);
+        self.errors = records.into_iter().collect();
+        self.truncate();
+        self.loaded = true;
+        Ok(())
+    }
+
+    fn truncate(&mut self) {
+        while self.errors.len() > ERROR_RING_SIZE {
+            self.errors.pop_front();
+        }
+    }
+
+    async fn record(&mut self, origin: ErrorOrigin, error: &ModuleError) -> ErrorRecord {
+        if let Err(err) = self.load().await {
+            error!("failed to load stored errors: {err:?}");
+        }
+
+        // ids are only taken from the stored counter, the in-memory one is a fallback for when
+        // the store cannot be written
+        self.next_id = match store::issue_id(self.next_id).await {
+            Ok(id) => id,
+            Err(err) => {
+                error!("failed to issue an error id: {err:?}");
+                self.next_id + 1
+            }
+        };
+        let record = ErrorRecord {
+            id: self.next_id,
+            timestamp: Utc::now().timestamp(),
+            guild: origin.guild,
+            channel: origin.channel,
+            module: origin.module,
+            user: origin.user,
+            command: origin.command,
+            kind: error.kind().to_string(),
+            detail: error.detail(),
+        };
+        error!("{}", record.summary());
+        self.errors.push_back(record.clone());
+        self.truncate();
+
+        if let Err(err) = store::store_record(&record).await {
+            error!("failed to store error #{}: {err:?}", record.id);
+        }
+        record
+    }
 }
 
 impl ErrorManager {
-    pub fn module_error(&mut self, module: &impl DragonBotModule, error: &ModuleError) {
-        let error_string = ErrorManager::get_module_error_string(module, error);
-
-        error!("{}", &error_string);
-        self.all_error_log.push(error_string.clone());
-        self.module_error_log
-            .entry(module.id().to_string())
-            .or_default()
-            .push(error_string);
+    /// logs the error, records it in memory and on disk and posts unexpected errors to the
+    /// report channels. returns the id of the record.
+    pub async fn report(ctx: &Context, origin: ErrorOrigin, error: &ModuleError) -> u64 {
+        let record = STATE.lock().await.record(origin, error).await;
+        if error.user_message().is_none() {
+            Self::post_report(ctx, &record).await;
+        }
+        record.id
+    }
+
+    /// the message shown to the user, unexpected errors are only shown as their record id.
+    pub fn user_message(error: &ModuleError, reference: u64) -> String {
+        error.user_message().unwrap_or_else(|| {
+            format!(
+                "Something went wrong. If this keeps happening, ask staff about error `#{reference}`."
+            )
+        })
+    }
+
+    /// newest first, of one guild or of every guild if `guild` is `None`.
+    pub async fn recent_errors(
+        &self,
+        guild: Option<GuildId>,
+        module: Option<&str>,
+    ) -> Vec<ErrorRecord> {
+        STATE
+            .lock()
+            .await
+            .errors
+            .iter()
+            .rev()
+            .filter(|record| guild.is_none() || record.guild == guild)
+            .filter(|record| module.is_none_or(|module| record.module == module))
+            .cloned()
+            .collect()
+    }
+
+    /// an error of one guild or of every guild, looked up on disk if no longer in memory.
+    pub async fn error(
+        &self,
+        guild: Option<GuildId>,
+        id: u64,
+    ) -> Result<Option<ErrorRecord>, ModuleError> {
+        let record = STATE
+            .lock()
+            .await
+            .errors
+            .iter()
+            .find(|record| record.id == id)
+            .cloned();
+        let record = match record {
+            Some(record) => Some(record),
+            None => store::stored_record(guild, id).await?,
+        };
+        Ok(record.filter(|record| guild.is_none() || record.guild == guild))
+    }
+
+    /// removes the errors of one guild or of every guild, returning how many were removed.
+    pub async fn clear_errors(&self, guild: Option<GuildId>) -> Result<usize, ModuleError> {
+        let mut state = STATE.lock().await;
+        let before = state.errors.len();
+        state
+            .errors
+            .retain(|record| guild.is_some() && record.guild != guild);
+        let cleared = store::clear_records(guild).await?;
+        Ok(cleared.max(before - state.errors.len()))
     }
 }
@@ -0,0 +1,46 @@
+use super::{ModuleError, UserFacingError};
+use serenity::futures::FutureExt;
+use std::{
+    any::Any,
+    fmt::{self, Display, Formatter},
+    panic::AssertUnwindSafe,
+};
+
+#[derive(Debug)]
+pub enum PanicError {
+    HandlerPanicked(String),
+}
+
+impl Display for PanicError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PanicError::HandlerPanicked(message) => write!(f, "handler panicked: {message}"),
+        }
+    }
+}
+
+impl UserFacingError for PanicError {
+    fn is_user_error(&self) -> bool {
+        false
+    }
+}
+
+pub fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "unknown panic".to_string()),
+    }
+}
+
+/// runs a module hook, a panic becomes a `PanicError` the caller records like any other error.
+pub async fn catch_panic<T>(
+    hook: impl Future<Output = Result<T, ModuleError>>,
+) -> Result<T, ModuleError> {
+    match AssertUnwindSafe(hook).catch_unwind().await {
+        Ok(result) => result,
+        Err(payload) => Err(PanicError::HandlerPanicked(panic_message(payload)))?,
+    }
+}
@@ -1,5 +1,24 @@
-use crate::core::permissions::DragonModulePermission;
+use crate::core::permissions::{DragonModulePermission, ModulePermission};
+use serenity::all::Permissions;
 
 use super::ErrorManager;
 
-impl DragonModulePermission for ErrorManager {}
+pub const VIEW_ERRORS: ModulePermission = ModulePermission::new(
+    "error-manager",
+    "view-errors",
+    "browse the module errors of a guild",
+)
+.discord_default(Permissions::MANAGE_GUILD);
+
+pub const CLEAR_ERRORS: ModulePermission = ModulePermission::new(
+    "error-manager",
+    "clear-errors",
+    "clear the module errors of a guild",
+)
+.discord_default(Permissions::MANAGE_GUILD);
+
+impl DragonModulePermission for ErrorManager {
+    async fn all_permissions(&self) -> Vec<ModulePermission> {
+        vec![VIEW_ERRORS, CLEAR_ERRORS]
+    }
+}
@@ -0,0 +1,347 @@
+use super::{
+    ErrorManager, ErrorRecord,
+    command::{detail_embed, truncate},
+};
+use crate::module::{config::DragonModuleConfigurable, errors::ModuleError};
+use log::warn;
+use serenity::all::{CacheHttp, ChannelId, Context, CreateEmbed, CreateMessage};
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+use tokio::time::{Instant, sleep_until};
+
+/// errors posted to a channel per window, identical errors are always grouped.
+const REPORT_WINDOW: Duration = Duration::from_secs(60);
+const REPORT_MAX_PER_WINDOW: usize = 5;
+const SUMMARY_LINE_MAX_LEN: usize = 150;
+const SUMMARY_MAX_LEN: usize = 4000;
+
+static WINDOWS: LazyLock<Mutex<HashMap<ChannelId, ReportWindow>>> = LazyLock::new(Default::default);
+
+struct ReportGroup {
+    first: u64,
+    summary: String,
+    posted: bool,
+    repeated: usize,
+}
+
+/// the errors of a channel since `started`.
+struct ReportWindow {
+    started: Instant,
+    posted: usize,
+    groups: HashMap<String, ReportGroup>,
+    flush_scheduled: bool,
+}
+
+/// what to do with a record counted in its channel's window.
+struct Admission {
+    /// the record is posted on its own, otherwise it is part of the window's summary.
+    post: bool,
+    /// the summary of the previous window, which ended before the record.
+    expired: Option<String>,
+    /// the start of the window whose summary is to be posted once it ends, set once per
+    /// window.
+    flush: Option<Instant>,
+}
+
+impl ReportWindow {
+    fn new(started: Instant) -> Self {
+        Self {
+            started,
+            posted: 0,
+            groups: HashMap::new(),
+            flush_scheduled: false,
+        }
+    }
+
+    /// the errors that were not posted, `None` if every error was.
+    fn summary(&self) -> Option<String> {
+        let mut groups: Vec<_> = self
+            .groups
+            .values()
+            .filter(|group| !group.posted || group.repeated > 0)
+            .collect();
+        if groups.is_empty() {
+            return None;
+        }
+        groups.sort_by_key(|group| group.first);
+
+        let mut summary = String::new();
+        for group in groups {
+            let line = match group.posted {
+                true => format!(
+                    "`#{}` repeated {} more times\n",
+                    group.first, group.repeated
+                ),
+                false => format!(
+                    "`#{}` occurred {} times: {}\n",
+                    group.first,
+                    group.repeated + 1,
+                    truncate(&group.summary, SUMMARY_LINE_MAX_LEN)
+                ),
+            };
+            if summary.len() + line.len() > SUMMARY_MAX_LEN {
+                summary.push('…');
+                break;
+            }
+            summary.push_str(&line);
+        }
+        Some(summary)
+    }
+}
+
+/// counts the record in the channel's window at `now`, starting a new window if the
+/// previous one ended.
+fn count_record(
+    windows: &mut HashMap<ChannelId, ReportWindow>,
+    channel: ChannelId,
+    record: &ErrorRecord,
+    now: Instant,
+) -> Admission {
+    let window = windows
+        .entry(channel)
+        .or_insert_with(|| ReportWindow::new(now));
+    let mut admission = Admission {
+        post: false,
+        expired: None,
+        flush: None,
+    };
+    if now.duration_since(window.started) >= REPORT_WINDOW {
+        admission.expired = std::mem::replace(window, ReportWindow::new(now)).summary();
+    }
+
+    let key = format!(
+        "{:?}|{}|{}|{}",
+        record.guild, record.module, record.kind, record.detail
+    );
+    match window.groups.get_mut(&key) {
+        Some(group) => group.repeated += 1,
+        None => {
+            let posted = window.posted < REPORT_MAX_PER_WINDOW;
+            window.groups.insert(
+                key,
+                ReportGroup {
+                    first: record.id,
+                    summary: record.summary(),
+                    posted,
+                    repeated: 0,
+                },
+            );
+            if posted {
+                window.posted += 1;
+                admission.post = true;
+                return admission;
+            }
+        }
+    }
+
+    if !window.flush_scheduled {
+        window.flush_scheduled = true;
+        admission.flush = Some(window.started);
+    }
+    admission
+}
+
+/// counts the record in the channel's window, returning whether it should be posted.
+fn admit(ctx: &Context, channel: ChannelId, record: &ErrorRecord) -> bool {
+    let admission = count_record(
+        &mut WINDOWS.lock().unwrap(),
+        channel,
+        record,
+        Instant::now(),
+    );
+    if let Some(summary) = admission.expired {
+        tokio::spawn(post_summary(ctx.clone(), channel, summary));
+    }
+    if let Some(started) = admission.flush {
+        tokio::spawn(flush(ctx.clone(), channel, started));
+    }
+    admission.post
+}
+
+/// posts the summary of the window once it ends, unless a new error already did.
+async fn flush(ctx: Context, channel: ChannelId, started: Instant) {
+    sleep_until(started + REPORT_WINDOW).await;
+    let summary = {
+        let mut windows = WINDOWS.lock().unwrap();
+        match windows.get(&channel) {
+            Some(window) if window.started == started => {
+                windows.remove(&channel).and_then(|window| window.summary())
+            }
+            _ => None,
+        }
+    };
+    if let Some(summary) = summary {
+        post_summary(ctx, channel, summary).await;
+    }
+}
+
+async fn post_summary(ctx: Context, channel: ChannelId, summary: String) {
+    let embed = CreateEmbed::new()
+        .title("Errors not posted in the last minute")
+        .description(summary);
+    post(&ctx, channel, embed).await;
+}
+
+async fn post(ctx: &Context, channel: ChannelId, embed: CreateEmbed) {
+    if let Err(err) = channel
+        .send_message(ctx.http(), CreateMessage::new().embed(embed))
+        .await
+    {
+        warn!("failed to post error report to {channel}: {err:?}");
+    }
+}
+
+impl ErrorManager {
+    /// the report channel of the guild, or of the channel the error happened in, and the
+    /// global staff channel.
+    async fn report_channels(
+        ctx: &Context,
+        record: &ErrorRecord,
+    ) -> Result<Vec<ChannelId>, ModuleError> {
+        let mut channels = vec![];
+        let config = match (record.guild, record.channel) {
+            (Some(guild), Some(channel)) => {
+                Some(Self::get_effective_config(ctx, guild, channel).await?)
+            }
+            (Some(guild), None) => Some(Self::get_guild_config(guild).await?),
+            (None, _) => None,
+        };
+        if let Some(channel) = config.and_then(|config| config.report_channel) {
+            channels.push(channel);
+        }
+        if let Some(channel) = Self::get_global_config().await?.staff_channel
+            && !channels.contains(&channel)
+        {
+            channels.push(channel);
+        }
+        Ok(channels)
+    }
+
+    /// posts the record to its report channels, rate limited per channel.
+    pub(super) async fn post_report(ctx: &Context, record: &ErrorRecord) {
+        let channels = match Self::report_channels(ctx, record).await {
+            Ok(channels) => channels,
+            Err(err) => {
+                warn!("failed to read error report channels: {err:?}");
+                return;
+            }
+        };
+        for channel in channels {
+            if admit(ctx, channel, record) {
+                post(ctx, channel, detail_embed(record)).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serenity::all::GuildId;
+
+    fn record(id: u64, detail: &str) -> ErrorRecord {
+        ErrorRecord {
+            id,
+            timestamp: 0,
+            guild: Some(GuildId::new(1)),
+            channel: None,
+            module: "tgdb".to_string(),
+            user: None,
+            command: None,
+            kind: "TgDbError".to_string(),
+            detail: detail.to_string(),
+        }
+    }
+
+    const CHANNEL: ChannelId = ChannelId::new(2);
+
+    #[test]
+    fn posts_up_to_the_limit_per_window() {
+        let mut windows = HashMap::new();
+        let now = Instant::now();
+        for id in 1..=REPORT_MAX_PER_WINDOW as u64 {
+            let admission = count_record(&mut windows, CHANNEL, &record(id, &id.to_string()), now);
+            assert!(admission.post);
+            assert!(admission.flush.is_none());
+        }
+
+        let over = count_record(&mut windows, CHANNEL, &record(100, "over"), now);
+        assert!(!over.post);
+        assert_eq!(over.flush, Some(now));
+        let again = count_record(&mut windows, CHANNEL, &record(101, "again"), now);
+        assert!(!again.post);
+        assert!(
+            again.flush.is_none(),
+            "the flush is scheduled once per window"
+        );
+
+        let summary = windows[&CHANNEL].summary().unwrap();
+        assert!(summary.contains("`#100` occurred 1 times"));
+        assert!(summary.contains("`#101` occurred 1 times"));
+    }
+
+    #[test]
+    fn groups_identical_errors() {
+        let mut windows = HashMap::new();
+        let now = Instant::now();
+        assert!(count_record(&mut windows, CHANNEL, &record(1, "refused"), now).post);
+        for id in 2..=4 {
+            assert!(!count_record(&mut windows, CHANNEL, &record(id, "refused"), now).post);
+        }
+        assert!(count_record(&mut windows, CHANNEL, &record(5, "timeout"), now).post);
+
+        let window = &windows[&CHANNEL];
+        assert_eq!(window.posted, 2);
+        assert_eq!(
+            window.summary().as_deref(),
+            Some("`#1` repeated 3 more times\n")
+        );
+    }
+
+    #[test]
+    fn windows_are_per_channel() {
+        let mut windows = HashMap::new();
+        let now = Instant::now();
+        assert!(count_record(&mut windows, CHANNEL, &record(1, "refused"), now).post);
+        assert!(count_record(&mut windows, ChannelId::new(3), &record(1, "refused"), now).post);
+    }
+
+    #[test]
+    fn rolls_over_after_the_window() {
+        let mut windows = HashMap::new();
+        let start = Instant::now();
+        count_record(&mut windows, CHANNEL, &record(1, "refused"), start);
+        count_record(&mut windows, CHANNEL, &record(2, "refused"), start);
+
+        let before_end = start + REPORT_WINDOW - Duration::from_millis(1);
+        let admission = count_record(&mut windows, CHANNEL, &record(3, "refused"), before_end);
+        assert!(!admission.post);
+        assert!(admission.expired.is_none());
+
+        let later = start + REPORT_WINDOW;
+        let admission = count_record(&mut windows, CHANNEL, &record(4, "refused"), later);
+        assert!(
+            admission.post,
+            "the error is posted again in the new window"
+        );
+        assert_eq!(
+            admission.expired.as_deref(),
+            Some("`#1` repeated 2 more times\n")
+        );
+        assert_eq!(windows[&CHANNEL].started, later);
+        assert!(windows[&CHANNEL].summary().is_none());
+    }
+
+    #[test]
+    fn no_summary_when_everything_was_posted() {
+        let mut windows = HashMap::new();
+        let now = Instant::now();
+        count_record(&mut windows, CHANNEL, &record(1, "refused"), now);
+        let later = now + REPORT_WINDOW;
+        let admission = count_record(&mut windows, CHANNEL, &record(2, "timeout"), later);
+        assert!(admission.expired.is_none());
+    }
+}
@@ -0,0 +1,156 @@
+use super::{ErrorRecord, ModuleError};
+use crate::{module::config::ConfigError, util::data_path};
+use chrono::{TimeDelta, Utc};
+use serenity::all::GuildId;
+use std::path::PathBuf;
+use tokio::{
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, write},
+    sync::Mutex,
+};
+
+/// errors kept on disk per guild, older ones are dropped on the next write.
+const STORE_MAX_ENTRIES: usize = 500;
+const STORE_RETENTION: TimeDelta = TimeDelta::days(30);
+
+static STORE_LOCK: Mutex<()> = Mutex::const_new(());
+
+async fn store_dir() -> Result<PathBuf, ModuleError> {
+    let path = data_path().await?.join("errors");
+    if !path.exists() {
+        create_dir_all(&path).await.map_err(ConfigError::IoError)?;
+    }
+    Ok(path)
+}
+
+/// `global.json` holds the errors outside of a guild.
+async fn store_file(guild: Option<GuildId>) -> Result<PathBuf, ModuleError> {
+    let name = match guild {
+        Some(guild) => guild.to_string(),
+        None => "global".to_string(),
+    };
+    Ok(store_dir().await?.join(format!("{name}.json")))
+}
+
+async fn read_file(path: PathBuf) -> Result<Vec<ErrorRecord>, ModuleError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let json = read_to_string(path).await.map_err(ConfigError::IoError)?;
+    Ok(serde_json::from_str(&json).map_err(ConfigError::SerdeError)?)
+}
+
+async fn write_file(guild: Option<GuildId>, records: &[ErrorRecord]) -> Result<(), ModuleError> {
+    let json = serde_json::to_string(records).map_err(ConfigError::SerdeError)?;
+    write(store_file(guild).await?, json)
+        .await
+        .map_err(ConfigError::IoError)?;
+    Ok(())
+}
+
+fn apply_retention(records: &mut Vec<ErrorRecord>) {
+    let oldest = (Utc::now() - STORE_RETENTION).timestamp();
+    records.retain(|record| record.timestamp >= oldest);
+    if records.len() > STORE_MAX_ENTRIES {
+        records.drain(..records.len() - STORE_MAX_ENTRIES);
+    }
+}
+
+pub async fn store_record(record: &ErrorRecord) -> Result<(), ModuleError> {
+    let _lock = STORE_LOCK.lock().await;
+    let mut records = read_file(store_file(record.guild).await?).await?;
+    records.push(record.clone());
+    apply_retention(&mut records);
+    write_file(record.guild, &records).await
+}
+
+/// the last issued id, kept apart from the records so ids are not reused once records are
+/// cleared or expire.
+async fn counter_file() -> Result<PathBuf, ModuleError> {
+    Ok(store_dir().await?.join("last-id"))
+}
+
+/// issues the id of a new error, above `after` and every id issued before.
+pub async fn issue_id(after: u64) -> Result<u64, ModuleError> {
+    let _lock = STORE_LOCK.lock().await;
+    let path = counter_file().await?;
+    let last: u64 = if path.exists() {
+        let raw = read_to_string(&path).await.map_err(ConfigError::IoError)?;
+        serde_json::from_str(&raw).map_err(ConfigError::SerdeError)?
+    } else {
+        // the counter file is missing, such as after it was removed by hand
+        read_records()
+            .await?
+            .last()
+            .map(|record| record.id)
+            .unwrap_or_default()
+    };
+    let id = last.max(after) + 1;
+    write(path, id.to_string())
+        .await
+        .map_err(ConfigError::IoError)?;
+    Ok(id)
+}
+
+/// every stored error within the retention, oldest first.
+pub async fn read_all_records() -> Result<Vec<ErrorRecord>, ModuleError> {
+    let _lock = STORE_LOCK.lock().await;
+    read_records().await
+}
+
+async fn read_records() -> Result<Vec<ErrorRecord>, ModuleError> {
+    let mut entries = read_dir(store_dir().await?)
+        .await
+        .map_err(ConfigError::IoError)?;
+    let mut records = vec![];
+    while let Some(entry) = entries.next_entry().await.map_err(ConfigError::IoError)? {
+        if entry.path().extension().is_some_and(|ext| ext == "json") {
+            records.extend(read_file(entry.path()).await?);
+        }
+    }
+    let oldest = (Utc::now() - STORE_RETENTION).timestamp();
+    records.retain(|record| record.timestamp >= oldest);
+    records.sort_by_key(|record| record.id);
+    Ok(records)
+}
+
+/// looks up an error that is no longer in memory.
+pub async fn stored_record(
+    guild: Option<GuildId>,
+    id: u64,
+) -> Result<Option<ErrorRecord>, ModuleError> {
+    let records = match guild {
+        Some(guild) => {
+            let _lock = STORE_LOCK.lock().await;
+            read_file(store_file(Some(guild)).await?).await?
+        }
+        None => {
+            let _lock = STORE_LOCK.lock().await;
+            read_records().await?
+        }
+    };
+    Ok(records.into_iter().find(|record| record.id == id))
+}
+
+/// removes the stored errors of one guild or of every guild, returning how many were removed.
+pub async fn clear_records(guild: Option<GuildId>) -> Result<usize, ModuleError> {
+    let _lock = STORE_LOCK.lock().await;
+    if let Some(guild) = guild {
+        let cleared = read_file(store_file(Some(guild)).await?).await?.len();
+        write_file(Some(guild), &[]).await?;
+        return Ok(cleared);
+    }
+
+    let mut cleared = 0;
+    let mut entries = read_dir(store_dir().await?)
+        .await
+        .map_err(ConfigError::IoError)?;
+    while let Some(entry) = entries.next_entry().await.map_err(ConfigError::IoError)? {
+        if entry.path().extension().is_some_and(|ext| ext == "json") {
+            cleared += read_file(entry.path()).await?.len();
+            remove_file(entry.path())
+                .await
+                .map_err(ConfigError::IoError)?;
+        }
+    }
+    Ok(cleared)
+}
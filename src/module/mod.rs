@@ -1,3 +1,4 @@
+pub mod bot_admin;
 pub mod commands;
 pub mod config;
 pub mod errors;
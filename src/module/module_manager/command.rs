@@ -1,11 +1,14 @@
-use super::{ModuleManager, permission::PERMISSION_MODULE_ACTIVATE};
+use super::{
+    ModuleManager,
+    permission::{PERMISSION_MODULE_ACTIVATE, PERMISSION_MODULE_DEACTIVATE},
+};
 use crate::{
     core::{
         commands::DragonModuleCommand,
         event_handler::ModuleEventHandler,
         module::{DragonBotModule, get_module_by_id},
         modules::DragonBotModuleInstance,
-        permissions::assert_permission,
+        permissions::ModulePermission,
     },
     module::errors::ModuleError,
 };
@@ -63,6 +66,15 @@ impl DragonModuleCommand for ModuleManager {
         )
     }
 
+    fn subcommand_permissions(&self) -> Vec<(&'static str, Option<ModulePermission>)> {
+        vec![
+            ("activate", Some(PERMISSION_MODULE_ACTIVATE)),
+            ("deactivate", Some(PERMISSION_MODULE_DEACTIVATE)),
+            ("list-all", None),
+            ("list-active", None),
+        ]
+    }
+
     async fn command_handle(
         &mut self,
         ctx: &Context,
@@ -77,75 +89,55 @@ impl DragonModuleCommand for ModuleManager {
         let guild = command.guild_id.expect("no guild id");
 
         match subcommand.name.as_str() {
-            "activate"
-                if assert_permission(
-                    ctx,
-                    command,
-                    command.member.as_ref().unwrap(),
-                    PERMISSION_MODULE_ACTIVATE,
-                )
-                .await? =>
-            {
-                match &subcommand.value {
-                    CommandDataOptionValue::SubCommand(target) => {
-                        let target = target
-                            .first()
-                            .expect("required field not present")
-                            .value
-                            .as_str()
-                            .expect("field malformed");
-                        self.set_module_active(guild, target).await?;
-                        if let Err(err) = command
-                            .create_followup(
-                                ctx.http(),
-                                CreateInteractionResponseFollowup::new()
-                                    .content(format!("Module `{target}` activated.")),
-                            )
-                            .await
-                        {
-                            warn!("Failed to send interaction response: {err}");
-                        }
-                        let module = get_module_by_id(target)?;
-                        ModuleEventHandler::register_guild_module_command(ctx, guild, module).await;
+            "activate" => match &subcommand.value {
+                CommandDataOptionValue::SubCommand(target) => {
+                    let target = target
+                        .first()
+                        .expect("required field not present")
+                        .value
+                        .as_str()
+                        .expect("field malformed");
+                    self.set_module_active(guild, target).await?;
+                    if let Err(err) = command
+                        .create_followup(
+                            ctx.http(),
+                            CreateInteractionResponseFollowup::new()
+                                .content(format!("Module `{target}` activated.")),
+                        )
+                        .await
+                    {
+                        warn!("Failed to send interaction response: {err}");
                     }
-                    _ => unreachable!(),
+                    let module = get_module_by_id(target)?;
+                    ModuleEventHandler::register_guild_module_command(ctx, guild, module).await;
                 }
-            }
-            "deactivate"
-                if assert_permission(
-                    ctx,
-                    command,
-                    command.member.as_ref().unwrap(),
-                    PERMISSION_MODULE_ACTIVATE,
-                )
-                .await? =>
-            {
-                match &subcommand.value {
-                    CommandDataOptionValue::SubCommand(target) => {
-                        let target = target
-                            .first()
-                            .expect("required field not present")
-                            .value
-                            .as_str()
-                            .expect("field malformed");
-                        self.set_module_inactive(command.guild_id.unwrap(), target)
-                            .await?;
-                        if let Err(err) = command
-                            .create_followup(
-                                ctx.http(),
-                                CreateInteractionResponseFollowup::new()
-                                    .content(format!("Module `{target}` deactivated.")),
-                            )
-                            .await
-                        {
-                            warn!("Failed to send interaction response: {err}");
-                        }
-                        let module = get_module_by_id(target)?;
-                        ModuleEventHandler::drop_guild_module_command(ctx, guild, module).await;
+                _ => unreachable!(),
+            },
+            "deactivate" => match &subcommand.value {
+                CommandDataOptionValue::SubCommand(target) => {
+                    let target = target
+                        .first()
+                        .expect("required field not present")
+                        .value
+                        .as_str()
+                        .expect("field malformed");
+                    self.set_module_inactive(command.guild_id.unwrap(), target)
+                        .await?;
+                    if let Err(err) = command
+                        .create_followup(
+                            ctx.http(),
+                            CreateInteractionResponseFollowup::new()
+                                .content(format!("Module `{target}` deactivated.")),
+                        )
+                        .await
+                    {
+                        warn!("Failed to send interaction response: {err}");
                     }
-                    _ => unreachable!(),
+                    let module = get_module_by_id(target)?;
+                    ModuleEventHandler::drop_guild_module_command(ctx, guild, module).await;
                 }
-            }
+                _ => unreachable!(),
+            },
             "list-active" => {
                 let mut response = "```diff\n".to_string();
                 let all_active = self.get_all_active_module_ids(guild).await?;
@@ -1,7 +1,10 @@
-use super::ModuleManager;
-use crate::module::config::{
-    DragonModuleConfigurable, ModuleConfig,
-    entry::{ConfigField, ConfigFieldError, ConfigValue},
+use super::{ModuleManager, permission::PERMISSION_MODULE_ACTIVATE};
+use crate::{
+    core::permissions::ModulePermission,
+    module::config::{
+        DragonModuleConfigurable, ModuleConfig,
+        entry::{ConfigField, ConfigFieldError, ConfigValue},
+    },
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -32,4 +35,8 @@ impl ModuleConfig for ModuleManagerConfig {
 impl DragonModuleConfigurable for ModuleManager {
     type Config = ModuleManagerConfig;
     type Module = ModuleManager;
+
+    fn config_edit_permission() -> Option<ModulePermission> {
+        Some(PERMISSION_MODULE_ACTIVATE)
+    }
 }
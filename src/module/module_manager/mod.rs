@@ -1,7 +1,14 @@
-use super::{config::DragonModuleConfigurable, errors::ModuleError};
-use crate::core::{module::DragonBotModule, modules::DragonBotModuleInstance};
-use log::info;
-use serenity::all::GuildId;
+use super::{
+    config::DragonModuleConfigurable,
+    errors::{ModuleError, UserFacingError},
+};
+use crate::{
+    core::{module::DragonBotModule, modules::DragonBotModuleInstance},
+    module::bot_admin::BotAdmin,
+};
+use log::{info, warn};
+use serenity::all::{Context, GuildId};
+use std::fmt::{self, Display, Formatter};
 
 mod command;
 pub mod config;
@@ -13,10 +20,35 @@ pub enum ModuleManagerError {
     ModuleAlreadyActive,
     ModuleAlreadyInactive,
     CannotInactivateManager,
+    ModuleAlwaysActive,
     LoadActiveFailed,
     ModuleNotFound,
 }
 
+impl Display for ModuleManagerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ModuleManagerError::ModuleNotActive => write!(f, "That module is not active."),
+            ModuleManagerError::ModuleAlreadyActive => write!(f, "That module is already active."),
+            ModuleManagerError::ModuleAlreadyInactive => {
+                write!(f, "That module is already inactive.")
+            }
+            ModuleManagerError::CannotInactivateManager => {
+                write!(f, "The module manager cannot be deactivated.")
+            }
+            ModuleManagerError::ModuleAlwaysActive => write!(f, "That module is always active."),
+            ModuleManagerError::LoadActiveFailed => write!(f, "failed to load the active modules"),
+            ModuleManagerError::ModuleNotFound => write!(f, "That module does not exist."),
+        }
+    }
+}
+
+impl UserFacingError for ModuleManagerError {
+    fn is_user_error(&self) -> bool {
+        !matches!(self, ModuleManagerError::LoadActiveFailed)
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct ModuleManager {}
 
@@ -30,6 +62,11 @@ impl DragonBotModule for ModuleManager {
 }
 
 impl<'a> ModuleManager {
+    /// modules active in every guild, their commands decide where they are registered.
+    pub fn always_active() -> [&'static str; 2] {
+        [Self::module_id(), BotAdmin::module_id()]
+    }
+
     pub async fn get_all_active_module_ids(
         &self,
         guild: GuildId,
@@ -38,6 +75,20 @@ impl<'a> ModuleManager {
         Ok(config.active.clone())
     }
 
+    /// the always active modules followed by the guild's active modules.
+    pub async fn get_command_module_ids(&self, guild: GuildId) -> Result<Vec<String>, ModuleError> {
+        let mut module_ids: Vec<String> = Self::always_active()
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        for active in self.get_all_active_module_ids(guild).await? {
+            if !module_ids.contains(&active) {
+                module_ids.push(active);
+            }
+        }
+        Ok(module_ids)
+    }
+
     pub async fn is_module_active<M>(&self, guild: GuildId) -> Result<bool, ModuleError>
     where
         M: DragonBotModule,
@@ -50,7 +101,7 @@ impl<'a> ModuleManager {
         guild: GuildId,
         module: &str,
     ) -> Result<bool, ModuleError> {
-        if module == Self::module_id() {
+        if Self::always_active().contains(&module) {
             return Ok(true);
         }
 
@@ -60,6 +111,19 @@ impl<'a> ModuleManager {
             .contains(&module.to_string()))
     }
 
+    /// the cached guilds the module is active in, bot-wide config changes only concern these.
+    pub async fn active_guilds(&self, ctx: &Context, module: &str) -> Vec<GuildId> {
+        let mut guilds = vec![];
+        for guild in ctx.cache.guilds() {
+            match self.is_module_id_active(guild, module).await {
+                Ok(true) => guilds.push(guild),
+                Ok(false) => {}
+                Err(err) => warn!("failed to check if {module} is active in {guild}: {err:?}"),
+            }
+        }
+        guilds
+    }
+
     pub async fn set_module_active(
         &'a mut self,
         guild: GuildId,
@@ -94,6 +158,11 @@ impl<'a> ModuleManager {
             unreachable!();
         }
 
+        if Self::always_active().contains(&module) {
+            Err(ModuleManagerError::ModuleAlwaysActive)?;
+            unreachable!();
+        }
+
         if !DragonBotModuleInstance::all_module_ids().contains(&module) {
             Err(ModuleManagerError::ModuleNotFound)?;
             unreachable!();
@@ -1,10 +1,13 @@
 use super::ModuleManager;
 use crate::core::permissions::{DragonModulePermission, ModulePermission};
+use serenity::all::Permissions;
 
 pub const PERMISSION_MODULE_ACTIVATE: ModulePermission =
-    ModulePermission::new("module-manager", "module-activate", "activate a module");
+    ModulePermission::new("module-manager", "module-activate", "activate a module")
+        .discord_default(Permissions::MANAGE_GUILD);
 pub const PERMISSION_MODULE_DEACTIVATE: ModulePermission =
-    ModulePermission::new("module-manager", "module-deactivate", "deactivate a module");
+    ModulePermission::new("module-manager", "module-deactivate", "deactivate a module")
+        .discord_default(Permissions::MANAGE_GUILD);
 
 impl DragonModulePermission for ModuleManager {
     async fn all_permissions(&self) -> Vec<ModulePermission> {
@@ -0,0 +1,243 @@
+use super::{PermissionsManager, guild_role_ids, mention, scope_suffix};
+use crate::{
+    module::{
+        config::{ConfigError, DragonModuleConfigurable},
+        errors::ModuleError,
+    },
+    util::data_path,
+};
+use chrono::Utc;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serenity::all::{
+    CacheHttp, ChannelId, Context, CreateAllowedMentions, CreateMessage, GuildId, UserId,
+};
+use std::{collections::HashSet, path::PathBuf};
+use tokio::{
+    fs::{create_dir_all, read_to_string, write},
+    sync::Mutex,
+};
+
+const AUDIT_MAX_ENTRIES: usize = 1000;
+
+static AUDIT_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditAction {
+    Grant,
+    Deny,
+    Revoke,
+    Lapse,
+    GroupCreate,
+    GroupDelete,
+    GroupAdd,
+    GroupRemove,
+    GroupAssign,
+    GroupUnassign,
+}
+
+/// a change to the permission tree, recorded by `PermissionsManager::audit`.
+pub struct PermissionChange {
+    pub action: AuditAction,
+    pub target: Option<u64>,
+    pub group: Option<String>,
+    pub namespace: Option<String>,
+    pub permission: Option<String>,
+    pub channel: Option<ChannelId>,
+    pub expires: Option<i64>,
+}
+
+impl PermissionChange {
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            action,
+            target: None,
+            group: None,
+            namespace: None,
+            permission: None,
+            channel: None,
+            expires: None,
+        }
+    }
+
+    pub fn target(mut self, target: u64) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn group(mut self, group: &str) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
+
+    pub fn permission(mut self, namespace: &str, permission: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self.permission = Some(permission.to_string());
+        self
+    }
+
+    pub fn channel(mut self, channel: Option<ChannelId>) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    pub fn expires(mut self, expires: Option<i64>) -> Self {
+        self.expires = expires;
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PermissionAuditEntry {
+    pub id: u64,
+    pub action: AuditAction,
+    /// `None` for changes made by the bot itself, such as lapsed grants.
+    pub actor: Option<UserId>,
+    pub target: Option<u64>,
+    pub group: Option<String>,
+    pub namespace: Option<String>,
+    pub permission: Option<String>,
+    pub channel: Option<ChannelId>,
+    pub expires: Option<i64>,
+    pub timestamp: i64,
+}
+
+impl PermissionAuditEntry {
+    pub fn describe(&self, roles: &HashSet<u64>) -> String {
+        let actor = self
+            .actor
+            .map(|actor| format!("<@{actor}>"))
+            .unwrap_or_else(|| "the bot".to_string());
+        let target = self
+            .target
+            .map(|target| mention(roles, target))
+            .unwrap_or_default();
+        let permission = format!(
+            "`{}:{}`",
+            self.namespace.as_deref().unwrap_or_default(),
+            self.permission.as_deref().unwrap_or_default()
+        );
+        let group = self.group.as_deref().unwrap_or_default();
+        let scope = scope_suffix(self.channel);
+        match self.action {
+            AuditAction::Grant => format!(
+                "{actor} granted {permission} to {target}{scope}{}",
+                self.expires
+                    .map(|expires| format!(" until <t:{expires}:f>"))
+                    .unwrap_or_default()
+            ),
+            AuditAction::Deny => format!("{actor} denied {permission} to {target}{scope}"),
+            AuditAction::Revoke => format!("{actor} revoked {permission} from {target}{scope}"),
+            AuditAction::Lapse => format!("grant of {permission} to {target}{scope} lapsed"),
+            AuditAction::GroupCreate => format!("{actor} created group `{group}`"),
+            AuditAction::GroupDelete => format!("{actor} deleted group `{group}`"),
+            AuditAction::GroupAdd => format!("{actor} added {permission} to group `{group}`"),
+            AuditAction::GroupRemove => {
+                format!("{actor} removed {permission} from group `{group}`")
+            }
+            AuditAction::GroupAssign => format!("{actor} assigned group `{group}` to {target}"),
+            AuditAction::GroupUnassign => {
+                format!("{actor} unassigned group `{group}` from {target}")
+            }
+        }
+    }
+}
+
+async fn audit_file(guild: GuildId) -> Result<PathBuf, ModuleError> {
+    let path = data_path().await?.join("permission-audit");
+    if !path.exists() {
+        create_dir_all(&path).await.map_err(ConfigError::IoError)?;
+    }
+    Ok(path.join(format!("{guild}.json")))
+}
+
+async fn read_audit_log(guild: GuildId) -> Result<Vec<PermissionAuditEntry>, ModuleError> {
+    let path = audit_file(guild).await?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let json = read_to_string(path).await.map_err(ConfigError::IoError)?;
+    Ok(serde_json::from_str(&json).map_err(ConfigError::SerdeError)?)
+}
+
+async fn record_audit(
+    guild: GuildId,
+    actor: Option<UserId>,
+    change: PermissionChange,
+) -> Result<PermissionAuditEntry, ModuleError> {
+    let _lock = AUDIT_LOCK.lock().await;
+    let mut log = read_audit_log(guild).await?;
+    let entry = PermissionAuditEntry {
+        id: log.last().map(|entry| entry.id + 1).unwrap_or(1),
+        action: change.action,
+        actor,
+        target: change.target,
+        group: change.group,
+        namespace: change.namespace,
+        permission: change.permission,
+        channel: change.channel,
+        expires: change.expires,
+        timestamp: Utc::now().timestamp(),
+    };
+    log.push(entry.clone());
+    if log.len() > AUDIT_MAX_ENTRIES {
+        log.drain(..log.len() - AUDIT_MAX_ENTRIES);
+    }
+
+    let json = serde_json::to_string(&log).map_err(ConfigError::SerdeError)?;
+    write(audit_file(guild).await?, json)
+        .await
+        .map_err(ConfigError::IoError)?;
+    Ok(entry)
+}
+
+/// newest entries first, optionally only those of a role or user or of a namespace.
+pub async fn audit_log(
+    guild: GuildId,
+    target: Option<u64>,
+    namespace: Option<&str>,
+) -> Result<Vec<PermissionAuditEntry>, ModuleError> {
+    let _lock = AUDIT_LOCK.lock().await;
+    let mut log: Vec<_> = read_audit_log(guild)
+        .await?
+        .into_iter()
+        .filter(|entry| target.is_none() || entry.target == target)
+        .filter(|entry| namespace.is_none() || entry.namespace.as_deref() == namespace)
+        .collect();
+    log.reverse();
+    Ok(log)
+}
+
+impl PermissionsManager {
+    /// records the change and posts it to the audit channel, channel scoped changes use the
+    /// audit channel set for their channel.
+    pub async fn audit(
+        ctx: &Context,
+        guild: GuildId,
+        actor: Option<UserId>,
+        change: PermissionChange,
+    ) -> Result<(), ModuleError> {
+        let entry = record_audit(guild, actor, change).await?;
+        let message = entry.describe(&guild_role_ids(ctx, guild));
+        info!("{guild}: {message}");
+
+        let config = match entry.channel {
+            Some(channel) => Self::get_effective_config(ctx, guild, channel).await?,
+            None => Self::get_guild_config(guild).await?,
+        };
+        if let Some(channel) = config.audit_channel
+            && let Err(err) = channel
+                .send_message(
+                    ctx.http(),
+                    CreateMessage::new()
+                        .content(format!("<t:{}:f> {message}", entry.timestamp))
+                        .allowed_mentions(CreateAllowedMentions::new()),
+                )
+                .await
+        {
+            warn!("failed to send permission audit message: {err:?}");
+        }
+        Ok(())
+    }
+}
@@ -1,22 +1,36 @@
-use super::PermissionsManager;
+use super::{
+    GRANTS_LOCK, GrantOptions, PermissionsError, PermissionsManager, audit::audit_log,
+    guild_role_ids, mention, scope_suffix,
+};
 use crate::{
     core::{
         commands::DragonModuleCommand,
         module::{DragonBotModule, get_module, get_module_by_id},
-        permissions::assert_permission,
+        permissions::{ModulePermission, assert_bot_owner},
     },
     module::{
-        errors::ModuleError, module_manager::ModuleManager,
-        permissions::permission::EDIT_PERMISSIONS,
+        commands::CommandError,
+        config::{DragonModuleConfigurable, entry::parse_duration},
+        errors::ModuleError,
+        module_manager::ModuleManager,
+        permissions::{
+            GLOBAL_WILDCARD, WILDCARD,
+            permission::{EDIT_PERMISSIONS, VIEW_PERMISSIONS},
+        },
     },
 };
+use chrono::Utc;
 use log::warn;
 use serenity::all::{
-    CacheHttp, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context,
-    CreateAllowedMentions, CreateCommand, CreateCommandOption, CreateInteractionResponseFollowup,
-    GuildId,
+    CacheHttp, ChannelId, ChannelType, CommandDataOption, CommandDataOptionValue,
+    CommandInteraction, CommandOptionType, Context, CreateAllowedMentions, CreateCommand,
+    CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponseFollowup,
+    GuildId, UserId,
 };
 
+const EMBED_DESCRIPTION_MAX_LEN: usize = 4000;
+const GROUP_NAME_MAX_LEN: u16 = 32;
+
 impl DragonModuleCommand for PermissionsManager {
     async fn command_builder(&self, guild: GuildId) -> Option<CreateCommand> {
         let mut builder = CreateCommand::new(self.id()).description("manage permissions");
@@ -40,10 +54,15 @@ impl DragonModuleCommand for PermissionsManager {
                 "grant",
                 "grant a permission",
             );
+            let mut deny_option = CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "deny",
+                "deny a permission, overriding grants of a lower precedence",
+            );
             let mut revoke_option = CreateCommandOption::new(
                 CommandOptionType::SubCommand,
                 "revoke",
-                "revoke a permission",
+                "remove a grant or deny of a permission",
             );
 
             let mut permission_option =
@@ -53,31 +72,184 @@ impl DragonModuleCommand for PermissionsManager {
                 permission_option =
                     permission_option.add_string_choice(permission.id(), permission.id());
             }
+            permission_option = permission_option
+                .add_string_choice(format!("{module_id}:* (every permission)"), WILDCARD)
+                .add_string_choice("*:* (every permission of every module)", GLOBAL_WILDCARD);
 
             let target_option =
                 CreateCommandOption::new(CommandOptionType::Mentionable, "target", "target")
                     .required(true);
+            let channel_option = CreateCommandOption::new(
+                CommandOptionType::Channel,
+                "channel",
+                "only in this channel or the channels of this category, guild wide if empty",
+            )
+            .channel_types(vec![
+                ChannelType::Text,
+                ChannelType::News,
+                ChannelType::Forum,
+                ChannelType::Category,
+            ]);
 
             grant_option = grant_option
                 .add_sub_option(permission_option.clone())
-                .add_sub_option(target_option.clone());
+                .add_sub_option(target_option.clone())
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "duration",
+                    "let the grant lapse after e.g. 7d or 12h, permanent if empty",
+                ))
+                .add_sub_option(channel_option.clone());
+            deny_option = deny_option
+                .add_sub_option(permission_option.clone())
+                .add_sub_option(target_option.clone())
+                .add_sub_option(channel_option.clone());
             revoke_option = revoke_option
-                .add_sub_option(permission_option)
-                .add_sub_option(target_option);
+                .add_sub_option(permission_option.clone())
+                .add_sub_option(target_option)
+                .add_sub_option(channel_option.clone());
+
+            let list_option = CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "list every grant and deny, grouped by role or user",
+            );
+            let who_has_option = CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "who-has",
+                "list the members holding a permission",
+            )
+            .add_sub_option(permission_option)
+            .add_sub_option(channel_option.clone());
+            let check_option = CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "check",
+                "show the effective permissions of a member and the rule behind each",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::User, "member", "the member")
+                    .required(true),
+            )
+            .add_sub_option(channel_option);
 
             module_option = module_option
                 .add_sub_option(grant_option)
-                .add_sub_option(revoke_option);
+                .add_sub_option(deny_option)
+                .add_sub_option(revoke_option)
+                .add_sub_option(list_option)
+                .add_sub_option(who_has_option)
+                .add_sub_option(check_option);
             builder = builder.add_option(module_option);
         }
 
+        let name_option = CreateCommandOption::new(CommandOptionType::String, "name", "the group")
+            .max_length(GROUP_NAME_MAX_LEN)
+            .required(true);
+        let group_permission_option = CreateCommandOption::new(
+            CommandOptionType::String,
+            "permission",
+            "`module:permission`, `module:*` or `*:*`",
+        )
+        .required(true);
+        let target_option =
+            CreateCommandOption::new(CommandOptionType::Mentionable, "target", "target")
+                .required(true);
+        let group_subcommand = |name: &str, description: &str| {
+            CreateCommandOption::new(CommandOptionType::SubCommand, name, description)
+                .add_sub_option(name_option.clone())
+        };
+        builder = builder.add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommandGroup,
+                "group",
+                "named bundles of permissions across modules",
+            )
+            .add_sub_option(group_subcommand(
+                "create",
+                "create an empty bot-wide group, bot owners only",
+            ))
+            .add_sub_option(group_subcommand(
+                "delete",
+                "delete a bot-wide group, bot owners only",
+            ))
+            .add_sub_option(
+                group_subcommand("add", "add a permission to a group, bot owners only")
+                    .add_sub_option(group_permission_option.clone()),
+            )
+            .add_sub_option(
+                group_subcommand(
+                    "remove",
+                    "remove a permission from a group, bot owners only",
+                )
+                .add_sub_option(group_permission_option),
+            )
+            .add_sub_option(
+                group_subcommand("assign", "give a role or user every permission of a group")
+                    .add_sub_option(target_option.clone()),
+            )
+            .add_sub_option(
+                group_subcommand("unassign", "take a group from a role or user")
+                    .add_sub_option(target_option),
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "list every group, its permissions and who it is assigned to",
+            )),
+        );
+        builder = builder.add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "audit",
+                "show the most recent permission changes",
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::Mentionable,
+                "target",
+                "only changes to this role or user",
+            ))
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "module",
+                "only changes to permissions of this module",
+            )),
+        );
+
         Some(builder)
     }
 
+    fn subcommand_permissions(&self) -> Vec<(&'static str, Option<ModulePermission>)> {
+        vec![
+            ("* grant", Some(EDIT_PERMISSIONS)),
+            ("* deny", Some(EDIT_PERMISSIONS)),
+            ("* revoke", Some(EDIT_PERMISSIONS)),
+            ("* list", Some(VIEW_PERMISSIONS)),
+            ("* who-has", Some(VIEW_PERMISSIONS)),
+            ("* check", Some(VIEW_PERMISSIONS)),
+            ("group *", Some(EDIT_PERMISSIONS)),
+            ("audit", Some(VIEW_PERMISSIONS)),
+        ]
+    }
+
     async fn command_handle(
         &mut self,
         ctx: &Context,
         command: &CommandInteraction,
+    ) -> Result<(), ModuleError> {
+        let guild = command.guild_id.expect("no guild id");
+        let _lock = GRANTS_LOCK.lock().await;
+        let had_grants = Self::get_full_config(guild).await?.has_grants();
+        let result = self.dispatch_command(ctx, command).await;
+        Self::refresh_command_visibility(ctx, guild, had_grants).await?;
+        result
+    }
+}
+
+impl PermissionsManager {
+    async fn dispatch_command(
+        &mut self,
+        ctx: &Context,
+        command: &CommandInteraction,
     ) -> Result<(), ModuleError> {
         let options = &command.data.options;
 
@@ -87,34 +259,74 @@ impl DragonModuleCommand for PermissionsManager {
         }
 
         let module = options.first().unwrap();
+        if let CommandDataOptionValue::SubCommand(data) = &module.value
+            && module.name == "audit"
+        {
+            return self.audit_command(ctx, command, data).await;
+        }
         if let CommandDataOptionValue::SubCommandGroup(operation) = &module.value {
             let operation = operation.first().unwrap();
 
             if let CommandDataOptionValue::SubCommand(data) = &operation.value {
-                let target = &data[data.iter().position(|i| i.name == "target").unwrap()]
-                    .value
-                    .as_mentionable()
-                    .unwrap();
-                let permission = &data[data.iter().position(|i| i.name == "permission").unwrap()]
-                    .value
-                    .as_str()
-                    .unwrap();
+                if module.name == "group" {
+                    return self
+                        .group_command(ctx, command, &operation.name, data)
+                        .await;
+                }
+
+                let option = |name: &str| {
+                    data.iter()
+                        .find(|option| option.name == name)
+                        .map(|option| &option.value)
+                };
+                let target = option("target").and_then(|value| value.as_mentionable());
+                let permission = option("permission").and_then(|value| value.as_str());
+                let namespace = match permission {
+                    Some(GLOBAL_WILDCARD) => WILDCARD,
+                    _ => module.name.as_str(),
+                };
+                let permission = permission.map(|permission| match permission {
+                    GLOBAL_WILDCARD => WILDCARD,
+                    permission => permission,
+                });
+                let user = option("member").and_then(|value| value.as_user_id());
+                let duration = option("duration").and_then(|value| value.as_str());
+                let channel = option("channel").and_then(|value| value.as_channel_id());
 
                 let member = command.member.as_ref().unwrap();
                 let guild = member.guild_id;
-                match operation.name.as_str() {
-                    "grant"
-                        if assert_permission(ctx, command, member, EDIT_PERMISSIONS).await? =>
-                    {
-                        self.give_permission_str(guild, *target, &module.name, permission)
-                            .await?;
+                match (operation.name.as_str(), target, permission) {
+                    ("grant", Some(target), Some(permission)) => {
+                        let expires = match duration {
+                            Some(duration) => Some(
+                                i64::try_from(parse_duration(duration)?.as_secs())
+                                    .ok()
+                                    .and_then(|secs| Utc::now().timestamp().checked_add(secs))
+                                    .ok_or(PermissionsError::DurationTooLong)?,
+                            ),
+                            None => None,
+                        };
+                        self.give_permission_str(
+                            ctx,
+                            target,
+                            namespace,
+                            permission,
+                            GrantOptions::new(member).scope(channel).expires(expires),
+                        )
+                        .await?;
                         if let Err(error) = command
                             .create_followup(
                                 ctx.http(),
                                 CreateInteractionResponseFollowup::new()
                                     .content(format!(
-                                        "Granted `{}:{}` to {}",
-                                        module.name, permission, target
+                                        "Granted `{}:{}` to {}{}{}",
+                                        namespace,
+                                        permission,
+                                        target,
+                                        scope_suffix(channel),
+                                        expires
+                                            .map(|expires| format!(" until <t:{expires}:f>"))
+                                            .unwrap_or_default()
                                     ))
                                     .allowed_mentions(CreateAllowedMentions::new()),
                             )
@@ -123,18 +335,21 @@ impl DragonModuleCommand for PermissionsManager {
                             warn!("failed to create followup: {error:?}");
                         }
                     }
-                    "revoke"
-                        if assert_permission(ctx, command, member, EDIT_PERMISSIONS).await? =>
-                    {
-                        self.take_permission_str(guild, *target, &module.name, permission)
-                            .await?;
+                    ("deny", Some(target), Some(permission)) => {
+                        self.deny_permission_str(
+                            ctx, member, target, channel, namespace, permission,
+                        )
+                        .await?;
                         if let Err(error) = command
                             .create_followup(
                                 ctx.http(),
                                 CreateInteractionResponseFollowup::new()
                                     .content(format!(
-                                        "Revoked `{}:{}` from {}",
-                                        module.name, permission, target
+                                        "Denied `{}:{}` to {}{}",
+                                        namespace,
+                                        permission,
+                                        target,
+                                        scope_suffix(channel)
                                     ))
                                     .allowed_mentions(CreateAllowedMentions::new()),
                             )
@@ -143,6 +358,41 @@ impl DragonModuleCommand for PermissionsManager {
                             warn!("failed to create followup: {error:?}");
                         }
                     }
+                    ("revoke", Some(target), Some(permission)) => {
+                        self.take_permission_str(
+                            ctx, member, target, channel, namespace, permission,
+                        )
+                        .await?;
+                        if let Err(error) = command
+                            .create_followup(
+                                ctx.http(),
+                                CreateInteractionResponseFollowup::new()
+                                    .content(format!(
+                                        "Revoked `{}:{}` from {}{}",
+                                        namespace,
+                                        permission,
+                                        target,
+                                        scope_suffix(channel)
+                                    ))
+                                    .allowed_mentions(CreateAllowedMentions::new()),
+                            )
+                            .await
+                        {
+                            warn!("failed to create followup: {error:?}");
+                        }
+                    }
+                    ("list", _, _) => {
+                        self.list_command(ctx, command, guild, &module.name).await?;
+                    }
+                    ("who-has", _, Some(permission)) => {
+                        self.who_has_command(ctx, command, guild, channel, namespace, permission)
+                            .await?;
+                    }
+                    ("check", _, _) => {
+                        let user = user.expect("missing member option");
+                        self.check_command(ctx, command, guild, channel, &module.name, user)
+                            .await?;
+                    }
                     _ => {}
                 }
             }
@@ -150,4 +400,308 @@ impl DragonModuleCommand for PermissionsManager {
 
         Ok(())
     }
+
+    async fn group_command(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+        operation: &str,
+        data: &[CommandDataOption],
+    ) -> Result<(), ModuleError> {
+        let option = |name: &str| {
+            data.iter()
+                .find(|option| option.name == name)
+                .map(|option| &option.value)
+        };
+        let name = option("name")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+        let permission = option("permission").and_then(|value| value.as_str());
+        let target = option("target").and_then(|value| value.as_mentionable());
+
+        // the definitions are shared by every guild
+        if matches!(operation, "create" | "delete" | "add" | "remove")
+            && !assert_bot_owner(ctx, command).await?
+        {
+            return Ok(());
+        }
+
+        let member = command.member.as_ref().unwrap();
+        let guild = member.guild_id;
+        let response = match (operation, permission, target) {
+            ("create", _, _) => {
+                self.create_group(ctx, member, name).await?;
+                format!("Created group `{name}`")
+            }
+            ("delete", _, _) => {
+                self.delete_group(ctx, member, name).await?;
+                format!("Deleted group `{name}`")
+            }
+            ("add", Some(permission), _) => {
+                self.add_group_permission(ctx, member, name, permission)
+                    .await?;
+                format!("Added `{permission}` to group `{name}`")
+            }
+            ("remove", Some(permission), _) => {
+                self.remove_group_permission(ctx, member, name, permission)
+                    .await?;
+                format!("Removed `{permission}` from group `{name}`")
+            }
+            ("assign", _, Some(target)) => {
+                self.assign_group(ctx, member, name, target).await?;
+                format!("Assigned group `{name}` to {target}")
+            }
+            ("unassign", _, Some(target)) => {
+                self.unassign_group(ctx, member, name, target).await?;
+                format!("Unassigned group `{name}` from {target}")
+            }
+            ("list", _, _) => return self.group_list_command(ctx, command, guild).await,
+            _ => return Ok(()),
+        };
+
+        if let Err(error) = command
+            .create_followup(
+                ctx.http(),
+                CreateInteractionResponseFollowup::new()
+                    .content(response)
+                    .allowed_mentions(CreateAllowedMentions::new()),
+            )
+            .await
+        {
+            warn!("failed to create followup: {error:?}");
+        }
+        Ok(())
+    }
+
+    async fn audit_command(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+        data: &[CommandDataOption],
+    ) -> Result<(), ModuleError> {
+        let member = command.member.as_ref().unwrap();
+        let option = |name: &str| {
+            data.iter()
+                .find(|option| option.name == name)
+                .map(|option| &option.value)
+        };
+        let target = option("target")
+            .and_then(|value| value.as_mentionable())
+            .map(|target| target.get());
+        let namespace = option("module").and_then(|value| value.as_str());
+
+        let guild = member.guild_id;
+        let roles = guild_role_ids(ctx, guild);
+        let mut lines: Vec<String> = audit_log(guild, target, namespace)
+            .await?
+            .iter()
+            .map(|entry| {
+                format!(
+                    "`#{}` <t:{}:R> {}",
+                    entry.id,
+                    entry.timestamp,
+                    entry.describe(&roles)
+                )
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push("No permission changes recorded.".to_string());
+        }
+
+        send_embed(
+            ctx,
+            command,
+            CreateEmbed::new()
+                .title("Permission changes")
+                .description(join_lines(&lines)),
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn group_list_command(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+        guild: GuildId,
+    ) -> Result<(), ModuleError> {
+        let guild_config = Self::resolution_config(guild).await?;
+        let roles = guild_role_ids(ctx, guild);
+
+        let mut lines = vec![];
+        for (name, group) in &guild_config.groups {
+            let assigned: Vec<String> = guild_config
+                .group_assignments
+                .get(name)
+                .into_iter()
+                .flatten()
+                .map(|id| mention(&roles, *id))
+                .collect();
+            lines.push(format!(
+                "`{name}`: `{}` → {}",
+                group.permissions.join("`, `"),
+                if assigned.is_empty() {
+                    "nobody".to_string()
+                } else {
+                    assigned.join(", ")
+                }
+            ));
+        }
+        if lines.is_empty() {
+            lines.push("No groups.".to_string());
+        }
+
+        send_embed(
+            ctx,
+            command,
+            CreateEmbed::new()
+                .title("Permission groups")
+                .description(join_lines(&lines)),
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn list_command(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+        guild: GuildId,
+        namespace: &str,
+    ) -> Result<(), ModuleError> {
+        let entries = self.list_namespace(guild, namespace).await?;
+        let roles = guild_role_ids(ctx, guild);
+
+        let mut lines = vec![];
+        for ((id, scope), entries) in &entries {
+            let mut line = format!("{}{}", mention(&roles, *id), scope_suffix(*scope));
+            if !entries.granted.is_empty() {
+                line.push_str(&format!(" granted: `{}`", entries.granted.join("`, `")));
+            }
+            if !entries.denied.is_empty() {
+                line.push_str(&format!(" denied: `{}`", entries.denied.join("`, `")));
+            }
+            lines.push(line);
+        }
+        if lines.is_empty() {
+            lines.push("No permissions granted or denied.".to_string());
+        }
+
+        send_embed(
+            ctx,
+            command,
+            CreateEmbed::new()
+                .title(format!("{namespace} permissions"))
+                .description(join_lines(&lines)),
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn who_has_command(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+        guild: GuildId,
+        channel: Option<ChannelId>,
+        namespace: &str,
+        permission: &str,
+    ) -> Result<(), ModuleError> {
+        let (holders, truncated) = self
+            .who_has(ctx, guild, channel, namespace, permission)
+            .await?;
+
+        let mut lines: Vec<String> = holders
+            .iter()
+            .map(|(user, rule)| format!("<@{user}> ({})", rule.describe()))
+            .collect();
+        if lines.is_empty() {
+            lines.push("No member holds this permission.".to_string());
+        }
+        let mut embed = CreateEmbed::new()
+            .title(format!("Members with `{namespace}:{permission}`"))
+            .description(join_lines(&lines));
+        if let Some(channel) = channel {
+            embed = embed.field("Channel", format!("<#{channel}>"), false);
+        }
+        if truncated {
+            embed = embed.footer(CreateEmbedFooter::new(
+                "only part of the member list was checked",
+            ));
+        }
+
+        send_embed(ctx, command, embed).await;
+        Ok(())
+    }
+
+    async fn check_command(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+        guild: GuildId,
+        channel: Option<ChannelId>,
+        namespace: &str,
+        user: UserId,
+    ) -> Result<(), ModuleError> {
+        let member = guild
+            .member(ctx.http(), user)
+            .await
+            .map_err(CommandError::from)?;
+        let permissions = get_module_by_id(namespace)?.all_permissions().await;
+        let rules = self
+            .explain_member(ctx, &member, channel, &permissions)
+            .await?;
+
+        let mut lines: Vec<String> = permissions
+            .iter()
+            .zip(rules)
+            .map(|(permission, rule)| {
+                let mark = if rule.allows() { "✅" } else { "❌" };
+                format!("{mark} `{}`: {}", permission.id(), rule.describe())
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push("This module has no permissions.".to_string());
+        }
+
+        let mut embed = CreateEmbed::new()
+            .title(format!(
+                "{namespace} permissions of {}",
+                member.display_name()
+            ))
+            .description(join_lines(&lines));
+        if let Some(channel) = channel {
+            embed = embed.field("Channel", format!("<#{channel}>"), false);
+        }
+        send_embed(ctx, command, embed).await;
+        Ok(())
+    }
+}
+
+/// joins as many lines as fit in an embed description.
+fn join_lines(lines: &[String]) -> String {
+    let mut description = String::new();
+    for (shown, line) in lines.iter().enumerate() {
+        if description.len() + line.len() + 1 > EMBED_DESCRIPTION_MAX_LEN {
+            description.push_str(&format!("... and {} more", lines.len() - shown));
+            break;
+        }
+        description.push_str(line);
+        description.push('\n');
+    }
+    description
+}
+
+async fn send_embed(ctx: &Context, command: &CommandInteraction, embed: CreateEmbed) {
+    if let Err(error) = command
+        .create_followup(
+            ctx.http(),
+            CreateInteractionResponseFollowup::new()
+                .embed(embed)
+                .allowed_mentions(CreateAllowedMentions::new()),
+        )
+        .await
+    {
+        warn!("failed to create followup: {error:?}");
+    }
 }
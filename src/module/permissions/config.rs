@@ -1,35 +1,331 @@
-use super::PermissionsManager;
-use crate::module::config::{
-    DragonModuleConfigurable, ModuleConfig,
-    entry::{ConfigField, ConfigFieldError, ConfigValue},
+use super::{GLOBAL_WILDCARD, PermissionsManager, WILDCARD, permission::EDIT_PERMISSIONS};
+use crate::{
+    core::permissions::ModulePermission,
+    module::config::{
+        DragonModuleConfigurable, ModuleConfig,
+        entry::{ConfigEntryType, ConfigField, ConfigFieldError, ConfigValue},
+    },
 };
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serenity::all::{ChannelId, ChannelType};
+use std::collections::{BTreeMap, HashMap};
+
+/// permission ids by role or user id, by module namespace.
+pub type PermissionTree = HashMap<String, HashMap<u64, Vec<String>>>;
+
+/// a named bundle of `module:permission` entries, defined bot-wide and assigned to roles or
+/// users per guild.
+#[derive(Serialize, Deserialize, Default)]
+pub struct PermissionGroup {
+    pub permissions: Vec<String>,
+}
+
+impl PermissionGroup {
+    pub fn grants(&self, namespace: &str, permission: &str) -> bool {
+        self.permissions.iter().any(|granted| {
+            granted == GLOBAL_WILDCARD
+                || granted.split_once(':').is_some_and(|(module, id)| {
+                    module == namespace && (id == permission || id == WILDCARD)
+                })
+        })
+    }
+}
+
+/// a grant in `namespaces` that lapses at `expires`, a unix timestamp.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GrantExpiry {
+    pub namespace: String,
+    pub id: u64,
+    pub permission: String,
+    pub expires: i64,
+    /// set if the grant is scoped to a channel.
+    #[serde(default)]
+    pub channel: Option<ChannelId>,
+}
+
+/// grants and denies that only apply in a channel, or every channel of a category.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ChannelScope {
+    #[serde(default)]
+    pub namespaces: PermissionTree,
+    #[serde(default)]
+    pub denies: PermissionTree,
+}
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct PermissionsManagerConfig {
-    pub namespaces: HashMap<String, HashMap<u64, Vec<String>>>,
+    pub namespaces: PermissionTree,
+    #[serde(default)]
+    pub denies: PermissionTree,
+    /// channel scoped entries, keyed by channel or category id.
+    #[serde(default)]
+    pub channels: HashMap<u64, ChannelScope>,
+    /// the group definitions, only kept in the bot-wide config.
+    #[serde(default)]
+    pub groups: BTreeMap<String, PermissionGroup>,
+    /// the roles and users each group is assigned to in the guild.
+    #[serde(default)]
+    pub group_assignments: BTreeMap<String, Vec<u64>>,
+    #[serde(default)]
+    pub expiries: Vec<GrantExpiry>,
+    #[serde(default)]
+    pub audit_channel: Option<ChannelId>,
+}
+
+impl PermissionsManagerConfig {
+    /// the grants of the channel scope, or the guild wide grants if `scope` is `None`.
+    pub fn grants(&self, scope: Option<ChannelId>) -> Option<&PermissionTree> {
+        match scope {
+            Some(channel) => self
+                .channels
+                .get(&channel.get())
+                .map(|scope| &scope.namespaces),
+            None => Some(&self.namespaces),
+        }
+    }
+
+    pub fn grants_mut(&mut self, scope: Option<ChannelId>) -> &mut PermissionTree {
+        match scope {
+            Some(channel) => &mut self.channels.entry(channel.get()).or_default().namespaces,
+            None => &mut self.namespaces,
+        }
+    }
+
+    /// the denies of the channel scope, or the guild wide denies if `scope` is `None`.
+    pub fn denies(&self, scope: Option<ChannelId>) -> Option<&PermissionTree> {
+        match scope {
+            Some(channel) => self.channels.get(&channel.get()).map(|scope| &scope.denies),
+            None => Some(&self.denies),
+        }
+    }
+
+    pub fn denies_mut(&mut self, scope: Option<ChannelId>) -> &mut PermissionTree {
+        match scope {
+            Some(channel) => &mut self.channels.entry(channel.get()).or_default().denies,
+            None => &mut self.denies,
+        }
+    }
+
+    pub fn is_granted(
+        &self,
+        scope: Option<ChannelId>,
+        namespace: &str,
+        id: u64,
+        permission: &str,
+    ) -> bool {
+        let Some(grants) = self.grants(scope) else {
+            return false;
+        };
+        [
+            (namespace, permission),
+            (namespace, WILDCARD),
+            (WILDCARD, WILDCARD),
+        ]
+        .into_iter()
+        .any(|(namespace, permission)| {
+            tree_contains(grants, namespace, id, permission)
+                && !self.is_expired(scope, namespace, id, permission)
+        })
+    }
+
+    fn expiry(
+        &self,
+        scope: Option<ChannelId>,
+        namespace: &str,
+        id: u64,
+        permission: &str,
+    ) -> Option<&GrantExpiry> {
+        self.expiries.iter().find(|expiry| {
+            expiry.channel == scope
+                && expiry.namespace == namespace
+                && expiry.id == id
+                && expiry.permission == permission
+        })
+    }
+
+    /// expired grants are ignored until they are pruned.
+    fn is_expired(
+        &self,
+        scope: Option<ChannelId>,
+        namespace: &str,
+        id: u64,
+        permission: &str,
+    ) -> bool {
+        self.expiry(scope, namespace, id, permission)
+            .is_some_and(|expiry| expiry.expires <= Utc::now().timestamp())
+    }
+
+    pub fn set_expiry(
+        &mut self,
+        scope: Option<ChannelId>,
+        namespace: &str,
+        id: u64,
+        permission: &str,
+        expires: Option<i64>,
+    ) {
+        self.expiries.retain(|expiry| {
+            expiry.channel != scope
+                || expiry.namespace != namespace
+                || expiry.id != id
+                || expiry.permission != permission
+        });
+        if let Some(expires) = expires {
+            self.expiries.push(GrantExpiry {
+                namespace: namespace.to_string(),
+                id,
+                permission: permission.to_string(),
+                expires,
+                channel: scope,
+            });
+        }
+    }
+
+    pub fn has_expiry(
+        &self,
+        scope: Option<ChannelId>,
+        namespace: &str,
+        id: u64,
+        permission: &str,
+    ) -> bool {
+        self.expiry(scope, namespace, id, permission).is_some()
+    }
+
+    /// removes lapsed grants, returning them.
+    pub fn prune_expired(&mut self) -> Vec<GrantExpiry> {
+        let now = Utc::now().timestamp();
+        let (expired, active) = self
+            .expiries
+            .drain(..)
+            .partition(|expiry| expiry.expires <= now);
+        self.expiries = active;
+        for expiry in &expired {
+            if let Some(permissions) = self
+                .grants_mut(expiry.channel)
+                .get_mut(&expiry.namespace)
+                .and_then(|namespace| namespace.get_mut(&expiry.id))
+            {
+                permissions.retain(|permission| *permission != expiry.permission);
+            }
+        }
+        self.prune_empty_scopes();
+        expired
+    }
+
+    /// drops channel scopes left without any entry.
+    pub fn prune_empty_scopes(&mut self) {
+        self.channels.retain(|_, scope| {
+            [&scope.namespaces, &scope.denies].into_iter().any(|tree| {
+                tree.values()
+                    .flat_map(|ids| ids.values())
+                    .any(|permissions| !permissions.is_empty())
+            })
+        });
+    }
+
+    pub fn is_denied(
+        &self,
+        scope: Option<ChannelId>,
+        namespace: &str,
+        id: u64,
+        permission: &str,
+    ) -> bool {
+        self.denies(scope)
+            .is_some_and(|denies| tree_matches(denies, namespace, id, permission))
+    }
+
+    /// whether any role or user is granted anything or assigned a group.
+    pub fn has_grants(&self) -> bool {
+        let tree_has_grants = |tree: &PermissionTree| {
+            tree.values()
+                .flat_map(|ids| ids.values())
+                .any(|permissions| !permissions.is_empty())
+        };
+        tree_has_grants(&self.namespaces)
+            || self
+                .channels
+                .values()
+                .any(|scope| tree_has_grants(&scope.namespaces))
+            || self
+                .group_assignments
+                .values()
+                .any(|assigned| !assigned.is_empty())
+    }
+
+    /// the first group assigned to the role or user that grants the permission, the
+    /// definitions must have been filled in by `PermissionsManager::resolution_config`.
+    pub fn granting_group(&self, namespace: &str, id: u64, permission: &str) -> Option<&str> {
+        self.group_assignments
+            .iter()
+            .find(|(name, assigned)| {
+                assigned.contains(&id)
+                    && self
+                        .groups
+                        .get(*name)
+                        .is_some_and(|group| group.grants(namespace, permission))
+            })
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// the permission itself, `module:*` or the global `*:*`.
+fn tree_matches(tree: &PermissionTree, namespace: &str, id: u64, permission: &str) -> bool {
+    tree_contains(tree, namespace, id, permission)
+        || tree_contains(tree, namespace, id, WILDCARD)
+        || tree_contains(tree, WILDCARD, id, WILDCARD)
+}
+
+fn tree_contains(tree: &PermissionTree, namespace: &str, id: u64, permission: &str) -> bool {
+    tree.get(namespace)
+        .and_then(|namespace| namespace.get(&id))
+        .is_some_and(|permissions| permissions.iter().any(|granted| granted == permission))
 }
 
 impl ModuleConfig for PermissionsManagerConfig {
     fn get_config_fields() -> HashMap<&'static str, ConfigField> {
-        HashMap::new()
+        [(
+            "audit_channel",
+            ConfigField::new(
+                ConfigEntryType::Channel(vec![ChannelType::Text]),
+                "channel every permission change is posted to, including lapsed grants",
+            )
+            .channel_overridable(),
+        )]
+        .into_iter()
+        .collect()
     }
 
     fn set_config_entry(
         &mut self,
-        _field: &str,
-        _value: ConfigValue,
+        field: &str,
+        value: ConfigValue,
     ) -> Result<(), ConfigFieldError> {
-        Err(ConfigFieldError::FieldNotFound)
+        match field {
+            "audit_channel" => {
+                self.audit_channel = Some(value.to_u64()?)
+                    .filter(|channel| *channel != 0)
+                    .map(ChannelId::new)
+            }
+            _ => return Err(ConfigFieldError::FieldNotFound),
+        }
+        Ok(())
     }
 
-    fn get_config_entry(&self, _field: &str) -> Result<ConfigValue, ConfigFieldError> {
-        Err(ConfigFieldError::FieldNotFound)
+    fn get_config_entry(&self, field: &str) -> Result<ConfigValue, ConfigFieldError> {
+        match field {
+            "audit_channel" => Ok(ConfigValue::U64(
+                self.audit_channel.map(|channel| channel.get()).unwrap_or(0),
+            )),
+            _ => Err(ConfigFieldError::FieldNotFound),
+        }
     }
 }
 
 impl DragonModuleConfigurable for PermissionsManager {
     type Config = PermissionsManagerConfig;
     type Module = PermissionsManager;
+
+    fn config_edit_permission() -> Option<ModulePermission> {
+        Some(EDIT_PERMISSIONS)
+    }
 }
@@ -0,0 +1,228 @@
+use super::{
+    GLOBAL_WILDCARD, PermissionsError, PermissionsManager, WILDCARD,
+    audit::{AuditAction, PermissionChange},
+};
+use crate::{
+    core::module::get_module_by_id,
+    module::{config::DragonModuleConfigurable, errors::ModuleError},
+};
+use serenity::all::{Context, GenericId, Member};
+
+/// accepts `module:permission`, `module:*` and `*:*`.
+async fn validate_permission(permission: &str) -> Result<(), ModuleError> {
+    if permission == GLOBAL_WILDCARD {
+        return Ok(());
+    }
+    let (namespace, id) = permission
+        .split_once(':')
+        .ok_or(PermissionsError::PermissionNotFound)?;
+    let module = get_module_by_id(namespace).map_err(|_| PermissionsError::PermissionNotFound)?;
+    if id != WILDCARD
+        && !module
+            .all_permissions()
+            .await
+            .iter()
+            .any(|permission| permission.id() == id)
+    {
+        Err(PermissionsError::PermissionNotFound)?;
+    }
+    Ok(())
+}
+
+fn split_permission(permission: &str) -> (&str, &str) {
+    permission.split_once(':').unwrap_or((permission, ""))
+}
+
+impl PermissionsManager {
+    /// groups are defined bot-wide, guilds only assign them.
+    pub async fn create_group(
+        &self,
+        ctx: &Context,
+        actor: &Member,
+        name: &str,
+    ) -> Result<(), ModuleError> {
+        let mut global_config = Self::get_global_config().await?;
+        if global_config.groups.contains_key(name) {
+            Err(PermissionsError::GroupAlreadyExists)?;
+        }
+        global_config
+            .groups
+            .insert(name.to_string(), Default::default());
+        Self::set_global_config(global_config).await?;
+
+        Self::audit(
+            ctx,
+            actor.guild_id,
+            Some(actor.user.id),
+            PermissionChange::new(AuditAction::GroupCreate).group(name),
+        )
+        .await
+    }
+
+    /// also drops the group's assignments in every guild, so a new group of the same name
+    /// starts out unassigned.
+    pub async fn delete_group(
+        &self,
+        ctx: &Context,
+        actor: &Member,
+        name: &str,
+    ) -> Result<(), ModuleError> {
+        let mut global_config = Self::get_global_config().await?;
+        global_config
+            .groups
+            .remove(name)
+            .ok_or(PermissionsError::GroupNotFound)?;
+        Self::set_global_config(global_config).await?;
+
+        for guild in ctx.cache.guilds() {
+            if !Self::config_file(guild).await?.exists() {
+                continue;
+            }
+            let mut guild_config = Self::get_full_config(guild).await?;
+            if guild_config.group_assignments.remove(name).is_some() {
+                Self::set_full_config(guild, guild_config).await?;
+            }
+        }
+
+        Self::audit(
+            ctx,
+            actor.guild_id,
+            Some(actor.user.id),
+            PermissionChange::new(AuditAction::GroupDelete).group(name),
+        )
+        .await
+    }
+
+    pub async fn add_group_permission(
+        &self,
+        ctx: &Context,
+        actor: &Member,
+        name: &str,
+        permission: &str,
+    ) -> Result<(), ModuleError> {
+        validate_permission(permission).await?;
+        let mut global_config = Self::get_global_config().await?;
+        let group = global_config
+            .groups
+            .get_mut(name)
+            .ok_or(PermissionsError::GroupNotFound)?;
+        if group
+            .permissions
+            .iter()
+            .any(|granted| granted == permission)
+        {
+            Err(PermissionsError::PermissionAlreadyGiven)?;
+        }
+        group.permissions.push(permission.to_string());
+        Self::set_global_config(global_config).await?;
+
+        let (namespace, id) = split_permission(permission);
+        Self::audit(
+            ctx,
+            actor.guild_id,
+            Some(actor.user.id),
+            PermissionChange::new(AuditAction::GroupAdd)
+                .group(name)
+                .permission(namespace, id),
+        )
+        .await
+    }
+
+    pub async fn remove_group_permission(
+        &self,
+        ctx: &Context,
+        actor: &Member,
+        name: &str,
+        permission: &str,
+    ) -> Result<(), ModuleError> {
+        let mut global_config = Self::get_global_config().await?;
+        let group = global_config
+            .groups
+            .get_mut(name)
+            .ok_or(PermissionsError::GroupNotFound)?;
+        if !group
+            .permissions
+            .iter()
+            .any(|granted| granted == permission)
+        {
+            Err(PermissionsError::PermissionNotGiven)?;
+        }
+        group.permissions.retain(|granted| granted != permission);
+        Self::set_global_config(global_config).await?;
+
+        let (namespace, id) = split_permission(permission);
+        Self::audit(
+            ctx,
+            actor.guild_id,
+            Some(actor.user.id),
+            PermissionChange::new(AuditAction::GroupRemove)
+                .group(name)
+                .permission(namespace, id),
+        )
+        .await
+    }
+
+    pub async fn assign_group(
+        &self,
+        ctx: &Context,
+        actor: &Member,
+        name: &str,
+        target: GenericId,
+    ) -> Result<(), ModuleError> {
+        if !Self::get_global_config().await?.groups.contains_key(name) {
+            Err(PermissionsError::GroupNotFound)?;
+        }
+        let guild = actor.guild_id;
+        let mut guild_config = Self::get_full_config(guild).await?;
+        let assigned = guild_config
+            .group_assignments
+            .entry(name.to_string())
+            .or_default();
+        if assigned.contains(&target.get()) {
+            Err(PermissionsError::GroupAlreadyAssigned)?;
+        }
+        assigned.push(target.get());
+        Self::set_full_config(guild, guild_config).await?;
+
+        Self::audit(
+            ctx,
+            guild,
+            Some(actor.user.id),
+            PermissionChange::new(AuditAction::GroupAssign)
+                .group(name)
+                .target(target.get()),
+        )
+        .await
+    }
+
+    pub async fn unassign_group(
+        &self,
+        ctx: &Context,
+        actor: &Member,
+        name: &str,
+        target: GenericId,
+    ) -> Result<(), ModuleError> {
+        let guild = actor.guild_id;
+        let mut guild_config = Self::get_full_config(guild).await?;
+        let assigned = guild_config
+            .group_assignments
+            .get_mut(name)
+            .filter(|assigned| assigned.contains(&target.get()))
+            .ok_or(PermissionsError::GroupNotAssigned)?;
+        assigned.retain(|id| *id != target.get());
+        if assigned.is_empty() {
+            guild_config.group_assignments.remove(name);
+        }
+        Self::set_full_config(guild, guild_config).await?;
+
+        Self::audit(
+            ctx,
+            guild,
+            Some(actor.user.id),
+            PermissionChange::new(AuditAction::GroupUnassign)
+                .group(name)
+                .target(target.get()),
+        )
+        .await
+    }
+}
@@ -0,0 +1,206 @@
+use super::{
+    GLOBAL_WILDCARD, PermissionRule, PermissionsManager, WILDCARD,
+    config::{PermissionTree, PermissionsManagerConfig},
+    discord_permissions, resolve_in_chain, roles_by_position, scope_chain, with_discord_default,
+};
+use crate::{
+    core::{module::get_module_by_id, permissions::ModulePermission},
+    module::{commands::CommandError, config::DragonModuleConfigurable, errors::ModuleError},
+};
+use serenity::all::{CacheHttp, ChannelId, Context, GuildId, Member, Permissions, UserId};
+use std::collections::BTreeMap;
+
+const MEMBER_PAGE_SIZE: u64 = 1000;
+const MEMBER_SCAN_MAX: usize = 10_000;
+
+/// the grants and denies of a single role or user in one scope.
+#[derive(Default)]
+pub struct PermissionEntries {
+    pub granted: Vec<String>,
+    pub denied: Vec<String>,
+}
+
+/// guild administrators hold every permission, mirroring `check_permission`.
+fn effective_rule(
+    ctx: &Context,
+    guild_config: &PermissionsManagerConfig,
+    member: &Member,
+    chain: &[ChannelId],
+    namespace: &str,
+    permission: &str,
+    default: Option<Permissions>,
+) -> PermissionRule {
+    let channel = chain.first().copied();
+    if discord_permissions(ctx, member, channel).administrator() {
+        return PermissionRule::Administrator;
+    }
+    let roles = roles_by_position(ctx, member);
+    let rule = resolve_in_chain(
+        guild_config,
+        chain,
+        member.user.id,
+        &roles,
+        namespace,
+        permission,
+    );
+    with_discord_default(ctx, member, channel, rule, default)
+}
+
+/// the Discord default of the permission, none for wildcards.
+async fn default_permissions(namespace: &str, permission: &str) -> Option<Permissions> {
+    get_module_by_id(namespace)
+        .ok()?
+        .all_permissions()
+        .await
+        .into_iter()
+        .find(|module_permission| module_permission.id() == permission)?
+        .default_permissions()
+}
+
+/// the entries of `namespace` in one tree, global wildcards included as `*:*`.
+fn namespace_entries<'a>(
+    tree: &'a PermissionTree,
+    namespace: &str,
+) -> impl Iterator<Item = (u64, Vec<String>)> + 'a {
+    let direct = tree
+        .get(namespace)
+        .into_iter()
+        .flatten()
+        .map(|(id, permissions)| (*id, permissions.clone()));
+    // global wildcards apply to every namespace
+    let global = tree
+        .get(WILDCARD)
+        .into_iter()
+        .flatten()
+        .filter(|(_, permissions)| permissions.iter().any(|permission| permission == WILDCARD))
+        .map(|(id, _)| (*id, vec![GLOBAL_WILDCARD.to_string()]));
+    direct.chain(global)
+}
+
+impl PermissionsManager {
+    /// every role or user with an entry in the namespace, by scope, guild wide first.
+    pub async fn list_namespace(
+        &self,
+        guild: GuildId,
+        namespace: &str,
+    ) -> Result<BTreeMap<(u64, Option<ChannelId>), PermissionEntries>, ModuleError> {
+        let guild_config = Self::get_full_config(guild).await?;
+        let mut entries: BTreeMap<(u64, Option<ChannelId>), PermissionEntries> = BTreeMap::new();
+        let scopes = [(None, &guild_config.namespaces, &guild_config.denies)]
+            .into_iter()
+            .chain(guild_config.channels.iter().map(|(channel, scope)| {
+                (
+                    Some(ChannelId::new(*channel)),
+                    &scope.namespaces,
+                    &scope.denies,
+                )
+            }));
+        for (scope, granted, denied) in scopes {
+            for (id, granted) in namespace_entries(granted, namespace) {
+                entries
+                    .entry((id, scope))
+                    .or_default()
+                    .granted
+                    .extend(granted);
+            }
+            for (id, denied) in namespace_entries(denied, namespace) {
+                entries
+                    .entry((id, scope))
+                    .or_default()
+                    .denied
+                    .extend(denied);
+            }
+        }
+        let groups = Self::get_global_config().await?.groups;
+        for (name, assigned) in &guild_config.group_assignments {
+            if groups.get(name).is_some_and(|group| {
+                group.permissions.iter().any(|permission| {
+                    permission == GLOBAL_WILDCARD
+                        || permission.starts_with(&format!("{namespace}:"))
+                })
+            }) {
+                for id in assigned {
+                    entries
+                        .entry((*id, None))
+                        .or_default()
+                        .granted
+                        .push(format!("group:{name}"));
+                }
+            }
+        }
+        entries.retain(|_, entries| !entries.granted.is_empty() || !entries.denied.is_empty());
+        Ok(entries)
+    }
+
+    /// members holding the permission in the channel and the rule granting it, scanning at
+    /// most `MEMBER_SCAN_MAX` members. the flag is set if the scan was cut short.
+    pub async fn who_has(
+        &self,
+        ctx: &Context,
+        guild: GuildId,
+        channel: Option<ChannelId>,
+        namespace: &str,
+        permission: &str,
+    ) -> Result<(Vec<(UserId, PermissionRule)>, bool), ModuleError> {
+        let guild_config = Self::resolution_config(guild).await?;
+        let chain = scope_chain(ctx, channel).await;
+        let default = default_permissions(namespace, permission).await;
+        let mut holders = vec![];
+        let mut scanned = 0;
+        let mut after = None;
+        loop {
+            let members = guild
+                .members(ctx.http(), Some(MEMBER_PAGE_SIZE), after)
+                .await
+                .map_err(CommandError::from)?;
+            scanned += members.len();
+            after = members.last().map(|member| member.user.id);
+            for member in &members {
+                let rule = effective_rule(
+                    ctx,
+                    &guild_config,
+                    member,
+                    &chain,
+                    namespace,
+                    permission,
+                    default,
+                );
+                if rule.allows() {
+                    holders.push((member.user.id, rule));
+                }
+            }
+            if (members.len() as u64) < MEMBER_PAGE_SIZE {
+                return Ok((holders, false));
+            }
+            if scanned >= MEMBER_SCAN_MAX {
+                return Ok((holders, true));
+            }
+        }
+    }
+
+    /// the rule deciding each of the permissions for the member in the channel.
+    pub async fn explain_member(
+        &self,
+        ctx: &Context,
+        member: &Member,
+        channel: Option<ChannelId>,
+        permissions: &[ModulePermission],
+    ) -> Result<Vec<PermissionRule>, ModuleError> {
+        let guild_config = Self::resolution_config(member.guild_id).await?;
+        let chain = scope_chain(ctx, channel).await;
+        Ok(permissions
+            .iter()
+            .map(|permission| {
+                effective_rule(
+                    ctx,
+                    &guild_config,
+                    member,
+                    &chain,
+                    permission.module(),
+                    permission.id(),
+                    permission.default_permissions(),
+                )
+            })
+            .collect())
+    }
+}
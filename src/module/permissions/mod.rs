@@ -1,12 +1,45 @@
-use super::{config::DragonModuleConfigurable, errors::ModuleError};
-use crate::core::{module::DragonBotModule, permissions::ModulePermission};
-use serenity::all::{GenericId, GuildId, Member};
-use std::collections::HashMap;
+use super::module_manager::ModuleManager;
+use super::{
+    config::{DragonModuleConfigurable, channel::channel_chain},
+    errors::{ErrorManager, ErrorOrigin, ModuleError, UserFacingError},
+};
+use crate::core::{
+    event_handler::ModuleEventHandler,
+    module::{DragonBotModule, get_module, get_module_by_id},
+    permissions::ModulePermission,
+};
+use audit::{AuditAction, PermissionChange};
+use config::{PermissionTree, PermissionsManagerConfig};
+use log::warn;
+use serenity::all::{ChannelId, Context, GenericId, GuildId, Member, Permissions, RoleId, UserId};
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use tokio::sync::Mutex;
 
+pub mod audit;
 mod command;
 pub mod config;
+mod group;
+mod inspect;
 mod permission;
 
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+static PRUNING: AtomicBool = AtomicBool::new(false);
+/// held while a command or the expiry check changes a guild's permissions, so neither
+/// overwrites the other's changes.
+static GRANTS_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// as a permission it matches every permission of the namespace, as a namespace every module.
+pub const WILDCARD: &str = "*";
+/// the command choice for a grant of every permission of every module.
+pub const GLOBAL_WILDCARD: &str = "*:*";
+
 #[derive(Default)]
 pub struct PermissionsManager;
 
@@ -17,6 +50,32 @@ impl DragonBotModule for PermissionsManager {
     {
         "permissions-manager"
     }
+
+    async fn init(&mut self, ctx: &Context) -> Result<(), ModuleError> {
+        if PRUNING.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                for guild in ctx.cache.guilds() {
+                    if let Err(err) = Self::prune_expired_grants(&ctx, guild).await {
+                        warn!("failed to prune expired grants of {guild}");
+                        ErrorManager::report(
+                            &ctx,
+                            ErrorOrigin::new(Self::module_id()).guild(guild),
+                            &err,
+                        )
+                        .await;
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -24,108 +83,765 @@ pub enum PermissionsError {
     PermissionNotFound,
     PermissionAlreadyGiven,
     PermissionNotGiven,
+    GroupNotFound,
+    GroupAlreadyExists,
+    GroupAlreadyAssigned,
+    GroupNotAssigned,
+    DurationTooLong,
+}
+
+impl Display for PermissionsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PermissionsError::PermissionNotFound => write!(f, "That permission does not exist."),
+            PermissionsError::PermissionAlreadyGiven => {
+                write!(f, "That permission is already given.")
+            }
+            PermissionsError::PermissionNotGiven => write!(f, "That permission is not given."),
+            PermissionsError::GroupNotFound => write!(f, "That group does not exist."),
+            PermissionsError::GroupAlreadyExists => {
+                write!(f, "A group with that name already exists.")
+            }
+            PermissionsError::GroupAlreadyAssigned => {
+                write!(f, "That group is already assigned to them.")
+            }
+            PermissionsError::GroupNotAssigned => {
+                write!(f, "That group is not assigned to them.")
+            }
+            PermissionsError::DurationTooLong => write!(f, "That duration is too long."),
+        }
+    }
+}
+
+impl UserFacingError for PermissionsError {
+    fn is_user_error(&self) -> bool {
+        true
+    }
+}
+
+/// how a grant is made, `actor` is recorded in the audit log.
+pub struct GrantOptions<'a> {
+    actor: &'a Member,
+    scope: Option<ChannelId>,
+    expires: Option<i64>,
+}
+
+impl<'a> GrantOptions<'a> {
+    pub fn new(actor: &'a Member) -> Self {
+        Self {
+            actor,
+            scope: None,
+            expires: None,
+        }
+    }
+
+    /// limits the grant to a channel or category.
+    pub fn scope(mut self, scope: Option<ChannelId>) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// a unix timestamp after which the grant lapses.
+    pub fn expires(mut self, expires: Option<i64>) -> Self {
+        self.expires = expires;
+        self
+    }
+}
+
+/// the entry that decided whether a member holds a permission.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PermissionRule {
+    UserDeny,
+    UserAllow,
+    UserGroup(String),
+    RoleDeny(RoleId),
+    RoleAllow(RoleId),
+    RoleGroup(RoleId, String),
+    /// a rule of the channel scope of the channel or one of its parents.
+    InChannel(ChannelId, Box<PermissionRule>),
+    /// no entry matched, the member holds the permission's Discord default.
+    DiscordDefault(Permissions),
+    /// guild administrators hold every permission.
+    Administrator,
+    Default,
+}
+
+impl PermissionRule {
+    pub fn describe(&self) -> String {
+        match self {
+            PermissionRule::UserDeny => "denied to the member".to_string(),
+            PermissionRule::UserAllow => "granted to the member".to_string(),
+            PermissionRule::UserGroup(group) => format!("member is in group `{group}`"),
+            PermissionRule::RoleDeny(role) => format!("denied to <@&{role}>"),
+            PermissionRule::RoleAllow(role) => format!("granted to <@&{role}>"),
+            PermissionRule::RoleGroup(role, group) => format!("<@&{role}> is in group `{group}`"),
+            PermissionRule::InChannel(channel, rule) => {
+                format!("{} in <#{channel}>", rule.describe())
+            }
+            PermissionRule::DiscordDefault(permissions) => format!(
+                "Discord default for {}",
+                permissions.get_permission_names().join(", ")
+            ),
+            PermissionRule::Administrator => "administrator".to_string(),
+            PermissionRule::Default => "not granted".to_string(),
+        }
+    }
+
+    pub fn allows(&self) -> bool {
+        match self {
+            PermissionRule::InChannel(_, rule) => rule.allows(),
+            rule => matches!(
+                rule,
+                PermissionRule::UserAllow
+                    | PermissionRule::UserGroup(_)
+                    | PermissionRule::RoleAllow(_)
+                    | PermissionRule::RoleGroup(..)
+                    | PermissionRule::DiscordDefault(_)
+                    | PermissionRule::Administrator
+            ),
+        }
+    }
+}
+
+/// the member's roles, highest position first, followed by `@everyone`.
+fn roles_by_position(ctx: &Context, member: &Member) -> Vec<RoleId> {
+    let mut roles = member.roles.clone();
+    if let Some(guild) = ctx.cache.guild(member.guild_id) {
+        roles.sort_by_key(|role| Reverse(guild.roles.get(role).map(|role| role.position)));
+    }
+    roles.push(member.guild_id.everyone_role());
+    roles
+}
+
+/// precedence, first match wins: user deny, user allow, role deny, role allow, default.
+/// groups count as an allow of whoever they are assigned to, they are not channel scoped.
+/// `roles` are checked in order, see `roles_by_position`.
+fn resolve_rule(
+    guild_config: &PermissionsManagerConfig,
+    scope: Option<ChannelId>,
+    user: UserId,
+    roles: &[RoleId],
+    namespace: &str,
+    permission: &str,
+) -> PermissionRule {
+    let group = |id: u64| {
+        scope
+            .is_none()
+            .then(|| guild_config.granting_group(namespace, id, permission))
+            .flatten()
+    };
+    if guild_config.is_denied(scope, namespace, user.get(), permission) {
+        return PermissionRule::UserDeny;
+    }
+    if guild_config.is_granted(scope, namespace, user.get(), permission) {
+        return PermissionRule::UserAllow;
+    }
+    if let Some(group) = group(user.get()) {
+        return PermissionRule::UserGroup(group.to_string());
+    }
+    if let Some(role) = roles
+        .iter()
+        .find(|role| guild_config.is_denied(scope, namespace, role.get(), permission))
+    {
+        return PermissionRule::RoleDeny(*role);
+    }
+    for role in roles {
+        if guild_config.is_granted(scope, namespace, role.get(), permission) {
+            return PermissionRule::RoleAllow(*role);
+        }
+        if let Some(group) = group(role.get()) {
+            return PermissionRule::RoleGroup(*role, group.to_string());
+        }
+    }
+    PermissionRule::Default
+}
+
+/// channel scopes are more specific than the guild, so the first scope along `chain`
+/// with a matching entry decides before the guild wide entries are considered.
+fn resolve_in_chain(
+    guild_config: &PermissionsManagerConfig,
+    chain: &[ChannelId],
+    user: UserId,
+    roles: &[RoleId],
+    namespace: &str,
+    permission: &str,
+) -> PermissionRule {
+    for channel in chain {
+        let rule = resolve_rule(
+            guild_config,
+            Some(*channel),
+            user,
+            roles,
+            namespace,
+            permission,
+        );
+        if rule != PermissionRule::Default {
+            return PermissionRule::InChannel(*channel, Box::new(rule));
+        }
+    }
+    resolve_rule(guild_config, None, user, roles, namespace, permission)
+}
+
+/// the member's Discord permissions, in the channel if it is known to the cache.
+fn discord_permissions(ctx: &Context, member: &Member, channel: Option<ChannelId>) -> Permissions {
+    if let Some(permissions) = member.permissions {
+        return permissions;
+    }
+    let Some(guild) = ctx.cache.guild(member.guild_id) else {
+        return Permissions::empty();
+    };
+    if let Some(channel) = channel.and_then(|channel| guild.channels.get(&channel)) {
+        return guild.user_permissions_in(channel, member);
+    }
+    if guild.owner_id == member.user.id {
+        return Permissions::all();
+    }
+    let permissions = member
+        .roles
+        .iter()
+        .chain([&member.guild_id.everyone_role()])
+        .filter_map(|role| guild.roles.get(role))
+        .fold(Permissions::empty(), |permissions, role| {
+            permissions | role.permissions
+        });
+    if permissions.administrator() {
+        Permissions::all()
+    } else {
+        permissions
+    }
+}
+
+/// falls back to the permission's Discord default if no entry decided.
+fn with_discord_default(
+    ctx: &Context,
+    member: &Member,
+    channel: Option<ChannelId>,
+    rule: PermissionRule,
+    default: Option<Permissions>,
+) -> PermissionRule {
+    match default {
+        Some(default)
+            if rule == PermissionRule::Default
+                && discord_permissions(ctx, member, channel).contains(default) =>
+        {
+            PermissionRule::DiscordDefault(default)
+        }
+        _ => rule,
+    }
+}
+
+/// the channel and its parents, nothing outside of a channel.
+async fn scope_chain(ctx: &Context, channel: Option<ChannelId>) -> Vec<ChannelId> {
+    match channel {
+        Some(channel) => channel_chain(ctx, channel).await,
+        None => vec![],
+    }
+}
+
+/// ` in #channel` for channel scoped entries.
+fn scope_suffix(scope: Option<ChannelId>) -> String {
+    scope
+        .map(|channel| format!(" in <#{channel}>"))
+        .unwrap_or_default()
+}
+
+fn guild_role_ids(ctx: &Context, guild: GuildId) -> HashSet<u64> {
+    ctx.cache
+        .guild(guild)
+        .map(|guild| guild.roles.keys().map(|role| role.get()).collect())
+        .unwrap_or_default()
+}
+
+/// permission entries only store the id, roles are told apart through the guild's roles.
+fn mention(roles: &HashSet<u64>, id: u64) -> String {
+    if roles.contains(&id) {
+        format!("<@&{id}>")
+    } else {
+        format!("<@{id}>")
+    }
+}
+
+fn tree_entry<'a>(
+    tree: &'a mut PermissionTree,
+    target: GenericId,
+    namespace: &str,
+) -> &'a mut Vec<String> {
+    tree.entry(namespace.to_string())
+        .or_default()
+        .entry(target.get())
+        .or_default()
 }
 
 impl PermissionsManager {
-    async fn has_permission_str(
+    /// drops lapsed grants from storage and reports them to the audit channel.
+    async fn prune_expired_grants(ctx: &Context, guild: GuildId) -> Result<(), ModuleError> {
+        let _lock = GRANTS_LOCK.lock().await;
+        if !Self::config_file(guild).await?.exists() {
+            return Ok(());
+        }
+        let mut guild_config = Self::get_full_config(guild).await?;
+        let had_grants = guild_config.has_grants();
+        let expired = guild_config.prune_expired();
+        if expired.is_empty() {
+            return Ok(());
+        }
+        Self::set_full_config(guild, guild_config).await?;
+        Self::refresh_command_visibility(ctx, guild, had_grants).await?;
+
+        for expiry in expired {
+            Self::audit(
+                ctx,
+                guild,
+                None,
+                PermissionChange::new(AuditAction::Lapse)
+                    .target(expiry.id)
+                    .permission(&expiry.namespace, &expiry.permission)
+                    .channel(expiry.channel),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// re-registers the guild's commands once it starts or stops granting anything, commands
+    /// are only hidden from members while nothing is granted.
+    async fn refresh_command_visibility(
+        ctx: &Context,
+        guild: GuildId,
+        had_grants: bool,
+    ) -> Result<(), ModuleError> {
+        if Self::get_full_config(guild).await?.has_grants() == had_grants {
+            return Ok(());
+        }
+        let module_manager = get_module::<ModuleManager>()?;
+        for module_id in module_manager
+            .module::<ModuleManager>()
+            .get_command_module_ids(guild)
+            .await?
+        {
+            ModuleEventHandler::register_guild_module_command(
+                ctx,
+                guild,
+                get_module_by_id(&module_id)?,
+            )
+            .await;
+        }
+        Ok(())
+    }
+
+    /// the guild's entries with the bot-wide group definitions, only for resolving permissions
+    /// and never written back.
+    async fn resolution_config(guild: GuildId) -> Result<PermissionsManagerConfig, ModuleError> {
+        let mut guild_config = Self::get_full_config(guild).await?;
+        guild_config.groups = Self::get_global_config().await?.groups;
+        Ok(guild_config)
+    }
+
+    async fn resolve_permission_str(
         &self,
+        ctx: &Context,
         member: &Member,
+        channel: Option<ChannelId>,
         namespace: &str,
         permission: &str,
-    ) -> Result<bool, ModuleError> {
-        let mut guild_config = Self::get_full_config(member.guild_id).await?;
-        for id in [member.user.id.get()]
-            .into_iter()
-            .chain(member.roles.iter().map(|role| role.get()))
-        {
-            if guild_config
-                .namespaces
-                .entry(namespace.to_string())
-                .or_default()
-                .get(&id)
-                .is_some_and(|granted| granted.contains(&permission.to_string()))
-            {
-                return Ok(true);
+    ) -> Result<PermissionRule, ModuleError> {
+        let guild_config = Self::resolution_config(member.guild_id).await?;
+        let roles = roles_by_position(ctx, member);
+        let chain = scope_chain(ctx, channel).await;
+        Ok(resolve_in_chain(
+            &guild_config,
+            &chain,
+            member.user.id,
+            &roles,
+            namespace,
+            permission,
+        ))
+    }
+
+    /// grants the permission, replacing a deny for the same target and scope. granting again
+    /// replaces the previous expiry.
+    async fn give_permission_str(
+        &self,
+        ctx: &Context,
+        target: GenericId,
+        namespace: &str,
+        permission: &str,
+        options: GrantOptions<'_>,
+    ) -> Result<(), ModuleError> {
+        let GrantOptions {
+            actor,
+            scope,
+            expires,
+        } = options;
+        let permission = permission.to_string();
+        let guild = actor.guild_id;
+        let mut guild_config = Self::get_full_config(guild).await?;
+
+        let expiring = guild_config.has_expiry(scope, namespace, target.get(), &permission);
+        let permissions = tree_entry(guild_config.grants_mut(scope), target, namespace);
+        if permissions.contains(&permission) {
+            if !expiring {
+                Err(PermissionsError::PermissionAlreadyGiven)?;
             }
+        } else {
+            permissions.push(permission.clone());
         }
+        tree_entry(guild_config.denies_mut(scope), target, namespace)
+            .retain(|perm| *perm != permission);
+        guild_config.set_expiry(scope, namespace, target.get(), &permission, expires);
+        Self::set_full_config(guild, guild_config).await?;
 
-        Ok(false)
+        Self::audit(
+            ctx,
+            guild,
+            Some(actor.user.id),
+            PermissionChange::new(AuditAction::Grant)
+                .target(target.get())
+                .permission(namespace, &permission)
+                .channel(scope)
+                .expires(expires),
+        )
+        .await
     }
 
-    async fn give_permission_str(
+    /// denies the permission, replacing a grant for the same target and scope.
+    async fn deny_permission_str(
         &self,
-        guild: GuildId,
+        ctx: &Context,
+        actor: &Member,
         target: GenericId,
+        scope: Option<ChannelId>,
         namespace: &str,
         permission: &str,
     ) -> Result<(), ModuleError> {
         let permission = permission.to_string();
+        let guild = actor.guild_id;
         let mut guild_config = Self::get_full_config(guild).await?;
 
-        let namespaces = &mut guild_config.namespaces;
-        let namespace = namespaces
-            .entry(namespace.to_string())
-            .or_insert_with(HashMap::new);
-
-        let permissions = namespace.entry(target.get()).or_insert_with(Vec::new);
+        let permissions = tree_entry(guild_config.denies_mut(scope), target, namespace);
         if permissions.contains(&permission) {
             Err(PermissionsError::PermissionAlreadyGiven)?;
             unreachable!()
         }
-        permissions.push(permission);
-        Self::set_full_config(guild, guild_config).await
+        permissions.push(permission.clone());
+        tree_entry(guild_config.grants_mut(scope), target, namespace)
+            .retain(|perm| *perm != permission);
+        guild_config.set_expiry(scope, namespace, target.get(), &permission, None);
+        Self::set_full_config(guild, guild_config).await?;
+
+        Self::audit(
+            ctx,
+            guild,
+            Some(actor.user.id),
+            PermissionChange::new(AuditAction::Deny)
+                .target(target.get())
+                .permission(namespace, &permission)
+                .channel(scope),
+        )
+        .await
     }
 
+    /// removes a grant or deny of the permission for the target in the scope.
     async fn take_permission_str(
         &self,
-        guild: GuildId,
+        ctx: &Context,
+        actor: &Member,
         target: GenericId,
+        scope: Option<ChannelId>,
         namespace: &str,
         permission: &str,
     ) -> Result<(), ModuleError> {
         let permission = permission.to_string();
+        let guild = actor.guild_id;
         let mut guild_config = Self::get_full_config(guild).await?;
 
-        let namespaces = &mut guild_config.namespaces;
-        let namespace = namespaces
-            .entry(namespace.to_string())
-            .or_insert_with(HashMap::new);
-
-        let permissions = namespace.entry(target.get()).or_insert_with(Vec::new);
-        if permissions.contains(&permission) {
+        let mut removed = false;
+        for denies in [false, true] {
+            let tree = match denies {
+                false => guild_config.grants_mut(scope),
+                true => guild_config.denies_mut(scope),
+            };
+            let permissions = tree_entry(tree, target, namespace);
+            let before = permissions.len();
+            permissions.retain(|perm| *perm != permission);
+            removed |= permissions.len() != before;
+        }
+        guild_config.prune_empty_scopes();
+        if !removed {
             Err(PermissionsError::PermissionNotGiven)?;
-            unreachable!()
         }
-        permissions.retain(|perm| *perm != permission);
-        Self::set_full_config(guild, guild_config).await
+        guild_config.set_expiry(scope, namespace, target.get(), &permission, None);
+        Self::set_full_config(guild, guild_config).await?;
+
+        Self::audit(
+            ctx,
+            guild,
+            Some(actor.user.id),
+            PermissionChange::new(AuditAction::Revoke)
+                .target(target.get())
+                .permission(namespace, &permission)
+                .channel(scope),
+        )
+        .await
     }
 
+    /// `channel` is where the permission is used, `None` only considers guild wide entries.
     pub async fn has_permission(
         &self,
+        ctx: &Context,
         member: &Member,
+        channel: Option<ChannelId>,
         permission: ModulePermission,
     ) -> Result<bool, ModuleError> {
-        self.has_permission_str(member, permission.module(), permission.id())
-            .await
+        Ok(self
+            .resolve_permission(ctx, member, channel, permission)
+            .await?
+            .allows())
+    }
+
+    pub async fn resolve_permission(
+        &self,
+        ctx: &Context,
+        member: &Member,
+        channel: Option<ChannelId>,
+        permission: ModulePermission,
+    ) -> Result<PermissionRule, ModuleError> {
+        let rule = self
+            .resolve_permission_str(ctx, member, channel, permission.module(), permission.id())
+            .await?;
+        Ok(with_discord_default(
+            ctx,
+            member,
+            channel,
+            rule,
+            permission.default_permissions(),
+        ))
     }
 
     pub async fn give_permission(
         &self,
-        guild: GuildId,
+        ctx: &Context,
         target: GenericId,
         permission: ModulePermission,
+        options: GrantOptions<'_>,
     ) -> Result<(), ModuleError> {
-        self.give_permission_str(guild, target, permission.module(), permission.id())
+        self.give_permission_str(ctx, target, permission.module(), permission.id(), options)
             .await
     }
 
+    pub async fn deny_permission(
+        &self,
+        ctx: &Context,
+        actor: &Member,
+        target: GenericId,
+        scope: Option<ChannelId>,
+        permission: ModulePermission,
+    ) -> Result<(), ModuleError> {
+        self.deny_permission_str(
+            ctx,
+            actor,
+            target,
+            scope,
+            permission.module(),
+            permission.id(),
+        )
+        .await
+    }
+
     pub async fn take_permission(
         &self,
-        guild: GuildId,
+        ctx: &Context,
+        actor: &Member,
         target: GenericId,
+        scope: Option<ChannelId>,
         permission: ModulePermission,
     ) -> Result<(), ModuleError> {
-        self.take_permission_str(guild, target, permission.module(), permission.id())
-            .await
+        self.take_permission_str(
+            ctx,
+            actor,
+            target,
+            scope,
+            permission.module(),
+            permission.id(),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::PermissionGroup;
+
+    const USER: UserId = UserId::new(10);
+    const HIGH_ROLE: RoleId = RoleId::new(20);
+    const LOW_ROLE: RoleId = RoleId::new(21);
+    const CHANNEL: ChannelId = ChannelId::new(30);
+    const CATEGORY: ChannelId = ChannelId::new(31);
+    const ROLES: [RoleId; 2] = [HIGH_ROLE, LOW_ROLE];
+
+    fn grant(config: &mut PermissionsManagerConfig, scope: Option<ChannelId>, id: u64, perm: &str) {
+        tree_entry(config.grants_mut(scope), GenericId::new(id), "tgdb").push(perm.to_string());
+    }
+
+    fn deny(config: &mut PermissionsManagerConfig, scope: Option<ChannelId>, id: u64, perm: &str) {
+        tree_entry(config.denies_mut(scope), GenericId::new(id), "tgdb").push(perm.to_string());
+    }
+
+    fn resolve(config: &PermissionsManagerConfig, chain: &[ChannelId]) -> PermissionRule {
+        resolve_in_chain(config, chain, USER, &ROLES, "tgdb", "query")
+    }
+
+    #[test]
+    fn nothing_matches_by_default() {
+        let config = PermissionsManagerConfig::default();
+        assert_eq!(resolve(&config, &[]), PermissionRule::Default);
+    }
+
+    #[test]
+    fn user_deny_beats_every_allow() {
+        let mut config = PermissionsManagerConfig::default();
+        grant(&mut config, None, USER.get(), "query");
+        grant(&mut config, None, HIGH_ROLE.get(), "query");
+        deny(&mut config, None, USER.get(), "query");
+        assert_eq!(resolve(&config, &[]), PermissionRule::UserDeny);
+    }
+
+    #[test]
+    fn user_allow_beats_role_deny() {
+        let mut config = PermissionsManagerConfig::default();
+        deny(&mut config, None, HIGH_ROLE.get(), "query");
+        grant(&mut config, None, USER.get(), "query");
+        assert_eq!(resolve(&config, &[]), PermissionRule::UserAllow);
+    }
+
+    #[test]
+    fn any_role_deny_beats_role_allows() {
+        let mut config = PermissionsManagerConfig::default();
+        grant(&mut config, None, HIGH_ROLE.get(), "query");
+        deny(&mut config, None, LOW_ROLE.get(), "query");
+        assert_eq!(resolve(&config, &[]), PermissionRule::RoleDeny(LOW_ROLE));
+    }
+
+    #[test]
+    fn highest_role_allow_decides() {
+        let mut config = PermissionsManagerConfig::default();
+        grant(&mut config, None, LOW_ROLE.get(), "query");
+        grant(&mut config, None, HIGH_ROLE.get(), WILDCARD);
+        assert_eq!(resolve(&config, &[]), PermissionRule::RoleAllow(HIGH_ROLE));
+    }
+
+    #[test]
+    fn groups_count_as_allows() {
+        let mut config = PermissionsManagerConfig::default();
+        config.groups.insert(
+            "db".to_string(),
+            PermissionGroup {
+                permissions: vec!["tgdb:query".to_string()],
+            },
+        );
+        config
+            .group_assignments
+            .insert("db".to_string(), vec![LOW_ROLE.get()]);
+        assert_eq!(
+            resolve(&config, &[]),
+            PermissionRule::RoleGroup(LOW_ROLE, "db".to_string())
+        );
+
+        config
+            .group_assignments
+            .get_mut("db")
+            .unwrap()
+            .push(USER.get());
+        assert_eq!(
+            resolve(&config, &[]),
+            PermissionRule::UserGroup("db".to_string())
+        );
+
+        deny(&mut config, None, LOW_ROLE.get(), "query");
+        assert_eq!(
+            resolve(&config, &[]),
+            PermissionRule::UserGroup("db".to_string())
+        );
+        config
+            .group_assignments
+            .get_mut("db")
+            .unwrap()
+            .retain(|id| *id != USER.get());
+        assert_eq!(resolve(&config, &[]), PermissionRule::RoleDeny(LOW_ROLE));
+    }
+
+    #[test]
+    fn groups_are_not_channel_scoped() {
+        let mut config = PermissionsManagerConfig::default();
+        config.groups.insert(
+            "db".to_string(),
+            PermissionGroup {
+                permissions: vec!["tgdb:*".to_string()],
+            },
+        );
+        config
+            .group_assignments
+            .insert("db".to_string(), vec![USER.get()]);
+        assert_eq!(
+            resolve_rule(&config, Some(CHANNEL), USER, &ROLES, "tgdb", "query"),
+            PermissionRule::Default
+        );
+    }
+
+    #[test]
+    fn channel_scope_beats_guild_entries() {
+        let mut config = PermissionsManagerConfig::default();
+        grant(&mut config, None, USER.get(), "query");
+        deny(&mut config, Some(CHANNEL), HIGH_ROLE.get(), "query");
+        assert_eq!(
+            resolve(&config, &[CHANNEL, CATEGORY]),
+            PermissionRule::InChannel(CHANNEL, Box::new(PermissionRule::RoleDeny(HIGH_ROLE)))
+        );
+        assert_eq!(resolve(&config, &[]), PermissionRule::UserAllow);
+    }
+
+    #[test]
+    fn channel_beats_its_category() {
+        let mut config = PermissionsManagerConfig::default();
+        deny(&mut config, Some(CATEGORY), USER.get(), "query");
+        assert_eq!(
+            resolve(&config, &[CHANNEL, CATEGORY]),
+            PermissionRule::InChannel(CATEGORY, Box::new(PermissionRule::UserDeny))
+        );
+
+        grant(&mut config, Some(CHANNEL), LOW_ROLE.get(), "query");
+        assert_eq!(
+            resolve(&config, &[CHANNEL, CATEGORY]),
+            PermissionRule::InChannel(CHANNEL, Box::new(PermissionRule::RoleAllow(LOW_ROLE)))
+        );
+    }
+
+    #[test]
+    fn unmatched_scopes_fall_through_to_the_guild() {
+        let mut config = PermissionsManagerConfig::default();
+        grant(&mut config, Some(ChannelId::new(99)), USER.get(), "query");
+        deny(&mut config, None, HIGH_ROLE.get(), "query");
+        assert_eq!(
+            resolve(&config, &[CHANNEL, CATEGORY]),
+            PermissionRule::RoleDeny(HIGH_ROLE)
+        );
+    }
+
+    #[test]
+    fn assignments_need_a_definition() {
+        let mut config = PermissionsManagerConfig::default();
+        config
+            .group_assignments
+            .insert("db".to_string(), vec![USER.get()]);
+        assert_eq!(resolve(&config, &[]), PermissionRule::Default);
+    }
+
+    #[test]
+    fn lapsed_grants_do_not_match() {
+        let mut config = PermissionsManagerConfig::default();
+        grant(&mut config, None, USER.get(), "query");
+        config.set_expiry(None, "tgdb", USER.get(), "query", Some(0));
+        assert_eq!(resolve(&config, &[]), PermissionRule::Default);
     }
 }
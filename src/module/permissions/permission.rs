@@ -1,4 +1,5 @@
 use crate::core::permissions::{DragonModulePermission, ModulePermission};
+use serenity::all::Permissions;
 
 use super::PermissionsManager;
 
@@ -6,10 +7,18 @@ pub const EDIT_PERMISSIONS: ModulePermission = ModulePermission::new(
     "permissions-manager",
     "edit-permissions",
     "edit the permission tree for a guild",
-);
+)
+.discord_default(Permissions::MANAGE_ROLES);
+
+pub const VIEW_PERMISSIONS: ModulePermission = ModulePermission::new(
+    "permissions-manager",
+    "view-permissions",
+    "inspect the permission tree for a guild",
+)
+.discord_default(Permissions::MANAGE_ROLES);
 
 impl DragonModulePermission for PermissionsManager {
     async fn all_permissions(&self) -> Vec<ModulePermission> {
-        vec![EDIT_PERMISSIONS]
+        vec![EDIT_PERMISSIONS, VIEW_PERMISSIONS]
     }
 }
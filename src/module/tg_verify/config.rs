@@ -8,6 +8,7 @@ use serenity::all::RoleId;
 use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct TgVerifyConfig {
     pub role_verified_linked: RoleId,
     pub role_verified_living: RoleId,
@@ -38,21 +39,24 @@ impl ModuleConfig for TgVerifyConfig {
                 ConfigField::new(
                     ConfigEntryType::U64,
                     "the playtime threshold for the verified living role",
-                ),
+                )
+                .inheritable(),
             ),
             (
                 "table_linking",
                 ConfigField::new(
                     ConfigEntryType::String,
                     "the table to query for discord links",
-                ),
+                )
+                .inheritable(),
             ),
             (
                 "table_playtime",
                 ConfigField::new(
                     ConfigEntryType::String,
                     "the table to query for player playtime",
-                ),
+                )
+                .inheritable(),
             ),
         ]
         .into_iter()
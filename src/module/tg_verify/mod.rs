@@ -1,6 +1,6 @@
 use super::{
-    config::DragonModuleConfigurable,
-    errors::ModuleError,
+    config::{DragonModuleConfigurable, entry::ConfigValue, history::module_history},
+    errors::{ErrorManager, ErrorOrigin, ModuleError},
     tgdb::{TgDb, TgDbError},
 };
 use crate::core::{
@@ -9,8 +9,9 @@ use crate::core::{
     permissions::DragonModulePermission,
 };
 use discord_link::ByondDiscordLink;
+use log::{debug, info, warn};
 use mysql::{params, prelude::Queryable};
-use serenity::all::GuildId;
+use serenity::all::{Context, GuildId, RoleId, UserId};
 
 pub mod config;
 mod discord_link;
@@ -25,18 +26,124 @@ impl DragonBotModule for TgVerify {
     {
         "tg-verify"
     }
+
+    async fn on_config_changed(
+        &mut self,
+        ctx: &Context,
+        guild: GuildId,
+        field: &str,
+    ) -> Result<(), ModuleError> {
+        if field != "role_verified_linked" {
+            return Ok(());
+        }
+
+        // the role edits can take longer than the interaction that changed the field
+        let previous = Self::previous_linked_role(guild).await?;
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            if let Err(err) = Self::sync_linked_role(&ctx, guild, previous).await {
+                warn!("failed to sync the linked role of {guild}");
+                ErrorManager::report(&ctx, ErrorOrigin::new(Self::module_id()).guild(guild), &err)
+                    .await;
+            }
+        });
+        Ok(())
+    }
 }
 
 impl DragonModulePermission for TgVerify {}
 impl DragonModuleCommand for TgVerify {}
 
 impl TgVerify {
+    pub async fn query_valid_links(
+        &self,
+        guild: GuildId,
+    ) -> Result<Vec<ByondDiscordLink>, ModuleError> {
+        let config = Self::get_guild_config(guild).await?;
+        let discord_link_table = &config.table_linking;
+
+        let tgdb = get_module::<TgDb>()?;
+        let tgdb: &TgDb = tgdb.module();
+        Ok(tgdb
+            .get_conn(guild)?
+            .exec(
+                "SELECT * FROM :discord_link_table WHERE valid = 1",
+                params! {
+                    discord_link_table,
+                },
+            )
+            .map_err(TgDbError::from)?)
+    }
+
+    /// the linked role before its last change, from the config history.
+    async fn previous_linked_role(guild: GuildId) -> Result<Option<RoleId>, ModuleError> {
+        let previous = module_history(guild, Self::module_id())
+            .await?
+            .into_iter()
+            .find(|entry| entry.field == "role_verified_linked" && entry.channel.is_none())
+            .and_then(|entry| entry.old);
+        Ok(match previous {
+            Some(ConfigValue::U64(role)) if role != 0 => Some(RoleId::new(role)),
+            _ => None,
+        })
+    }
+
+    /// gives the linked role to every member with a valid link and takes the previous linked
+    /// role from them.
+    pub async fn sync_linked_role(
+        ctx: &Context,
+        guild: GuildId,
+        previous: Option<RoleId>,
+    ) -> Result<(), ModuleError> {
+        let role = Some(Self::get_guild_config(guild).await?.role_verified_linked)
+            .filter(|role| *role != RoleId::default());
+        let previous = previous.filter(|previous| Some(*previous) != role);
+        if role.is_none() && previous.is_none() {
+            return Ok(());
+        }
+
+        let tg_verify = get_module::<TgVerify>()?;
+        let links = tg_verify
+            .module::<TgVerify>()
+            .query_valid_links(guild)
+            .await?;
+        let mut synced = 0;
+        let mut failed = 0;
+        for link in links.iter().filter(|link| link.discord_id != 0) {
+            let member = UserId::new(link.discord_id);
+            let mut result = Ok(());
+            if let Some(previous) = previous {
+                result = ctx
+                    .http
+                    .remove_member_role(guild, member, previous, Some("linked role changed"))
+                    .await;
+            }
+            if let Some(role) = role
+                && result.is_ok()
+            {
+                result = ctx
+                    .http
+                    .add_member_role(guild, member, role, Some("linked role changed"))
+                    .await;
+            }
+            match result {
+                Ok(_) => synced += 1,
+                Err(err) => {
+                    failed += 1;
+                    debug!("failed to sync linked role of {member}: {err}");
+                }
+            }
+        }
+        info!("synced linked role of {synced} members in {guild}, {failed} failed");
+        Ok(())
+    }
+
     pub async fn query_ckey(
         &self,
         guild: GuildId,
         ckey: &str,
     ) -> Result<Vec<ByondDiscordLink>, ModuleError> {
-        let config = Self::get_full_config(guild).await?;
+        let config = Self::get_guild_config(guild).await?;
         let discord_link_table = &config.table_linking;
 
         let tgdb = get_module::<TgDb>()?;
@@ -50,7 +157,7 @@ impl TgVerify {
                     ckey,
                 },
             )
-            .map_err(TgDbError::MysqlError)?)
+            .map_err(TgDbError::from)?)
     }
 
     pub async fn query_discord_id(
@@ -58,7 +165,7 @@ impl TgVerify {
         guild: GuildId,
         discord_id: u64,
     ) -> Result<Vec<ByondDiscordLink>, ModuleError> {
-        let config = Self::get_full_config(guild).await?;
+        let config = Self::get_guild_config(guild).await?;
         let discord_link_table = &config.table_linking;
 
         let tgdb = get_module::<TgDb>()?;
@@ -72,7 +179,7 @@ impl TgVerify {
                     discord_id,
                 },
             )
-            .map_err(TgDbError::MysqlError)?)
+            .map_err(TgDbError::from)?)
     }
 
     pub async fn query_link_token(
@@ -80,7 +187,7 @@ impl TgVerify {
         guild: GuildId,
         token: &str,
     ) -> Result<Option<ByondDiscordLink>, ModuleError> {
-        let config = Self::get_full_config(guild).await?;
+        let config = Self::get_guild_config(guild).await?;
         let discord_link_table = &config.table_linking;
 
         let tgdb = get_module::<TgDb>()?;
@@ -94,7 +201,7 @@ impl TgVerify {
                     token,
                 },
             )
-            .map_err(TgDbError::MysqlError)?)
+            .map_err(TgDbError::from)?)
     }
 
     pub async fn update_link(
@@ -102,7 +209,7 @@ impl TgVerify {
         guild: GuildId,
         link: &ByondDiscordLink,
     ) -> Result<(), ModuleError> {
-        let config = Self::get_full_config(guild).await?;
+        let config = Self::get_guild_config(guild).await?;
         let discord_link_table = &config.table_linking;
         let id = &link.id;
 
@@ -117,7 +224,7 @@ impl TgVerify {
                     id,
                 },
             )
-            .map_err(TgDbError::MysqlError)?;
+            .map_err(TgDbError::from)?;
 
         if result.is_none() {
             return Err(ModuleError::TgDbError(TgDbError::InternalError(
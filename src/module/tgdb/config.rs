@@ -1,19 +1,24 @@
 use std::collections::HashMap;
 
-use crate::module::config::{
-    DragonModuleConfigurable, ModuleConfig,
-    entry::{ConfigEntryType, ConfigField, ConfigFieldError, ConfigValue},
+use crate::{
+    core::permissions::ModulePermission,
+    module::config::{
+        DragonModuleConfigurable, ModuleConfig,
+        entry::{ConfigEntryType, ConfigField, ConfigFieldError, ConfigValue},
+        secret::Secret,
+    },
 };
 
-use super::TgDb;
+use super::{TgDb, permission::MANAGE_CONNECTION};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct TgDbConfig {
     pub address: String,
     pub port: u64,
     pub user: String,
-    pub password_b64: String,
+    pub password_b64: Secret,
     pub database: String,
 }
 
@@ -22,26 +27,30 @@ impl ModuleConfig for TgDbConfig {
         [
             (
                 "address",
-                ConfigField::new(ConfigEntryType::String, "address to the database"),
+                ConfigField::new(ConfigEntryType::String, "address to the database").inheritable(),
             ),
             (
                 "user",
-                ConfigField::new(ConfigEntryType::String, "username for the client"),
+                ConfigField::new(ConfigEntryType::String, "username for the client").inheritable(),
             ),
             (
                 "password_b64",
                 ConfigField::new(
                     ConfigEntryType::String,
                     "base64 hash of the password for the client",
-                ),
+                )
+                .secret()
+                .inheritable(),
             ),
             (
                 "database",
-                ConfigField::new(ConfigEntryType::String, "name of the database to use"),
+                ConfigField::new(ConfigEntryType::String, "name of the database to use")
+                    .inheritable(),
             ),
             (
                 "port",
-                ConfigField::new(ConfigEntryType::U64, "port to use for the database"),
+                ConfigField::new(ConfigEntryType::U64, "port to use for the database")
+                    .inheritable(),
             ),
         ]
         .into_iter()
@@ -53,7 +62,7 @@ impl ModuleConfig for TgDbConfig {
             "address" => Ok(ConfigValue::String(self.address.clone())),
             "port" => Ok(ConfigValue::U64(self.port)),
             "user" => Ok(ConfigValue::String(self.user.clone())),
-            "password_b64" => Ok(ConfigValue::String(self.password_b64.clone())),
+            "password_b64" => Ok(ConfigValue::String(self.password_b64.expose().to_string())),
             "database" => Ok(ConfigValue::String(self.database.clone())),
             _ => Err(ConfigFieldError::FieldNotFound),
         }
@@ -68,7 +77,7 @@ impl ModuleConfig for TgDbConfig {
             "address" => self.address = value.to_string()?,
             "port" => self.port = value.to_u64()?,
             "user" => self.user = value.to_string()?,
-            "password_b64" => self.password_b64 = value.to_string()?,
+            "password_b64" => self.password_b64 = Secret::new(value.to_string()?),
             "database" => self.database = value.to_string()?,
             _ => return Err(ConfigFieldError::FieldNotFound),
         }
@@ -79,4 +88,12 @@ impl ModuleConfig for TgDbConfig {
 impl DragonModuleConfigurable for TgDb {
     type Config = TgDbConfig;
     type Module = TgDb;
+
+    fn config_view_permission() -> Option<ModulePermission> {
+        Some(MANAGE_CONNECTION)
+    }
+
+    fn config_edit_permission() -> Option<ModulePermission> {
+        Some(MANAGE_CONNECTION)
+    }
 }
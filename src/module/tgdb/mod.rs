@@ -1,28 +1,79 @@
 pub mod config;
+mod permission;
 
-use super::errors::ModuleError;
-use crate::core::{
-    commands::DragonModuleCommand, module::DragonBotModule, permissions::DragonModulePermission,
+use super::{
+    config::DragonModuleConfigurable,
+    errors::{ErrorManager, ErrorOrigin, ModuleError, UserFacingError},
+};
+use crate::{
+    core::{commands::DragonModuleCommand, module::DragonBotModule},
+    util::get_all_guilds,
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use log::{info, warn};
+use mysql::{OptsBuilder, Pool, PooledConn};
+use serenity::all::{Context, GuildId};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    sync::RwLock,
+    time::Duration,
 };
-use mysql::{Pool, PooledConn};
-use serenity::all::GuildId;
-use std::{collections::HashMap, time::Duration};
 
 #[derive(Default)]
 pub struct TgDb {
-    pool: HashMap<GuildId, Option<Pool>>,
+    pool: RwLock<HashMap<GuildId, Pool>>,
 }
 
 impl TgDb {
     pub fn get_conn(&self, guild: GuildId) -> Result<PooledConn, ModuleError> {
-        Ok(self
+        // cloned so the lock is not held while waiting for a connection
+        let pool = self
             .pool
+            .read()
+            .unwrap()
             .get(&guild)
-            .ok_or(TgDbError::NotConnected)?
-            .as_ref()
-            .ok_or(TgDbError::NotConnected)?
+            .cloned()
+            .ok_or(TgDbError::NotConnected)?;
+        Ok(pool
             .try_get_conn(Duration::from_secs(5))
-            .map_err(TgDbError::MysqlError)?)
+            .map_err(TgDbError::from)?)
+    }
+
+    /// replaces the guild's pool with one built from its current config.
+    pub async fn connect(&self, guild: GuildId) -> Result<(), ModuleError> {
+        let config = Self::get_guild_config(guild).await?;
+        if config.address.is_empty() {
+            self.pool.write().unwrap().remove(&guild);
+            return Ok(());
+        }
+
+        let password = BASE64_STANDARD
+            .decode(config.password_b64.expose())
+            .ok()
+            .and_then(|password| String::from_utf8(password).ok())
+            .ok_or(TgDbError::InternalError(
+                "password is not valid base64".to_string(),
+            ))?;
+        let port = u16::try_from(config.port).map_err(|_| {
+            TgDbError::InternalError(format!("port {} is out of range", config.port))
+        })?;
+        let opts = OptsBuilder::new()
+            .ip_or_hostname(Some(config.address))
+            .tcp_port(port)
+            .user(Some(config.user))
+            .pass(Some(password))
+            .db_name(Some(config.database));
+
+        // drop the old pool first so a failed connect does not leave stale credentials in use
+        self.pool.write().unwrap().remove(&guild);
+        let pool = tokio::task::spawn_blocking(move || Pool::new(opts))
+            .await
+            .map_err(|err| TgDbError::InternalError(err.to_string()))?
+            .map_err(TgDbError::from)?;
+        self.pool.write().unwrap().insert(guild, pool);
+        info!("tgdb connected for {guild}");
+        Ok(())
     }
 }
 
@@ -33,13 +84,71 @@ impl DragonBotModule for TgDb {
     {
         "tgdb"
     }
+
+    /// connects every guild with a configured database, a guild that fails to connect does
+    /// not keep the others from connecting.
+    async fn init(&mut self, ctx: &Context) -> Result<(), ModuleError> {
+        for guild in get_all_guilds(ctx).await? {
+            if let Err(err) = self.connect(guild.id).await {
+                warn!("tgdb failed to connect for {}", guild.id);
+                ErrorManager::report(
+                    ctx,
+                    ErrorOrigin::new(Self::module_id()).guild(guild.id),
+                    &err,
+                )
+                .await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn on_config_changed(
+        &mut self,
+        _ctx: &Context,
+        guild: GuildId,
+        field: &str,
+    ) -> Result<(), ModuleError> {
+        match field {
+            "address" | "port" | "user" | "password_b64" | "database" => self.connect(guild).await,
+            _ => Ok(()),
+        }
+    }
+
+    async fn on_config_reloaded(
+        &mut self,
+        _ctx: &Context,
+        guild: GuildId,
+    ) -> Result<(), ModuleError> {
+        self.connect(guild).await
+    }
 }
-impl DragonModulePermission for TgDb {}
 impl DragonModuleCommand for TgDb {}
 
 #[derive(Debug)]
 pub enum TgDbError {
-    MysqlError(mysql::Error),
+    MysqlError(Box<mysql::Error>),
     InternalError(String),
     NotConnected,
 }
+
+impl From<mysql::Error> for TgDbError {
+    fn from(value: mysql::Error) -> Self {
+        Self::MysqlError(Box::new(value))
+    }
+}
+
+impl Display for TgDbError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TgDbError::MysqlError(err) => write!(f, "database error: {err}"),
+            TgDbError::InternalError(err) => write!(f, "internal database error: {err}"),
+            TgDbError::NotConnected => write!(f, "The database is not connected, try again later."),
+        }
+    }
+}
+
+impl UserFacingError for TgDbError {
+    fn is_user_error(&self) -> bool {
+        matches!(self, TgDbError::NotConnected)
+    }
+}
@@ -0,0 +1,14 @@
+use super::TgDb;
+use crate::core::permissions::{DragonModulePermission, ModulePermission};
+
+pub const MANAGE_CONNECTION: ModulePermission = ModulePermission::new(
+    "tgdb",
+    "manage-connection",
+    "view and edit the database connection config",
+);
+
+impl DragonModulePermission for TgDb {
+    async fn all_permissions(&self) -> Vec<ModulePermission> {
+        vec![MANAGE_CONNECTION]
+    }
+}
@@ -17,6 +17,15 @@ pub async fn config_path(guild: &GuildId) -> Result<PathBuf, ModuleError> {
     Ok(path)
 }
 
+/// config shared by every guild, see `DragonModuleConfigurable::get_global_config`.
+pub async fn global_config_path() -> Result<PathBuf, ModuleError> {
+    let path = data_path().await?.join("config").join("global");
+    if !path.exists() {
+        create_dir_all(&path).map_err(ConfigError::IoError).await?;
+    }
+    Ok(path)
+}
+
 pub async fn data_path() -> Result<PathBuf, ModuleError> {
     debug!("data_path");
     if !DATA_PATH.initialized() {
@@ -70,7 +79,7 @@ pub async fn get_all_guilds(ctx: &Context) -> Result<Vec<GuildInfo>, ModuleError
                 Some(100),
             )
             .await
-            .map_err(CommandError::Serenity)?;
+            .map_err(CommandError::from)?;
 
         guilds.append(&mut fetched);
         if fetched.len() < 100 {